@@ -1,38 +1,126 @@
+mod auth;
+mod commands;
+mod components;
+mod console;
 mod job_status;
+mod job_thread;
+mod plate_map;
+mod power_off;
+mod retry;
+mod timelapse;
 mod typemap;
+mod upload;
 
 use std::{
     future::{Future, IntoFuture},
     pin::Pin,
     sync::{
-        atomic::{AtomicBool, Ordering},
+        atomic::{AtomicBool, AtomicU64, Ordering},
         Arc,
     },
+    time::Duration,
 };
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use job_status::JobStatusMessage;
+use job_thread::JobThread;
+use power_off::PowerOff;
+use retry::{is_unknown_channel, retry};
 use serenity::{
     all::{
-        ActivityData, Channel, ChannelId, ChannelType, Context, CreateAllowedMentions,
-        CreateAttachment, CreateMessage, CreateThread, EventHandler, GatewayIntents, GuildChannel,
-        Mention, Message, OnlineStatus, Ready, UserId,
+        ActivityData, Channel, ChannelId, Context, CreateAllowedMentions, CreateAttachment,
+        CreateEmbed, CreateMessage, EventHandler, GatewayIntents, Interaction, Mention, Message,
+        OnlineStatus, Ready, UserId,
     },
     async_trait, Client,
 };
+use timelapse::Timelapse;
 use tokio::{
     select,
-    sync::{mpsc, watch, Mutex},
+    sync::{broadcast, mpsc, watch, Mutex},
+    time::{self, Instant},
 };
 use typemap::*;
 
 use crate::moonraker::{self, State};
 
+const TIMELAPSE_FILE_NAME: &str = "timelapse.gif";
+const TIMELAPSE_ROOT: &str = "timelapse";
+const RESTART_DELAY: Duration = Duration::from_secs(10);
+const THREAD_RETRY_DELAY: Duration = Duration::from_secs(60);
+
 #[derive(Debug, Clone, serde::Deserialize)]
 pub struct Config {
     pub token: String,
     pub user_id: u64,
     pub channel_id: u64,
+    #[serde(default)]
+    pub snapshot_mode: SnapshotMode,
+    /// Render the periodic snapshots of a job to a GIF when it completes.
+    #[serde(default)]
+    pub timelapse: bool,
+    /// Largest attachment in bytes that may be uploaded to the channel.
+    #[serde(default = "default_attachment_limit")]
+    pub attachment_limit: u64,
+    /// Least number of seconds between edits of the job status message.
+    #[serde(default = "default_edit_interval")]
+    pub edit_interval: u64,
+    /// Roles that may control the printer, in addition to the owner.
+    #[serde(default)]
+    pub admin_roles: Vec<u64>,
+    /// G-code macros offered as buttons by `/macros`.
+    #[serde(default)]
+    pub macros: Vec<Macro>,
+    pub auto_power_off: Option<AutoPowerOffConfig>,
+    /// Upload G-code files attached to messages in the prints channel. Needs
+    /// the privileged message content intent enabled for the bot.
+    #[serde(default)]
+    pub attachment_uploads: bool,
+}
+
+/// Switch a power device off after a job completed and the printer cooled down.
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct AutoPowerOffConfig {
+    pub device: String,
+    /// Extruder temperature in °C below which the device may be switched off.
+    #[serde(default = "default_max_temperature")]
+    pub max_temperature: f64,
+    /// Seconds to wait after the job completed.
+    #[serde(default = "default_power_off_delay")]
+    pub delay: u64,
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct Macro {
+    pub label: String,
+    pub gcode: String,
+}
+
+fn default_attachment_limit() -> u64 {
+    10 * 1024 * 1024
+}
+
+fn default_edit_interval() -> u64 {
+    5
+}
+
+fn default_max_temperature() -> f64 {
+    50.0
+}
+
+fn default_power_off_delay() -> u64 {
+    10 * 60
+}
+
+/// Where periodic snapshots are posted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SnapshotMode {
+    /// Post each snapshot as a message in the job thread.
+    #[default]
+    Thread,
+    /// Replace the image of the job status embed.
+    Embed,
 }
 
 pub struct ServiceBuilder {
@@ -54,7 +142,11 @@ impl IntoFuture for ServiceBuilder {
             let user_id = UserId::new(self.config.user_id);
             let channel_id = ChannelId::new(self.config.channel_id);
 
-            let client = Client::builder(self.config.token, GatewayIntents::default())
+            let mut intents = GatewayIntents::default();
+            if self.config.attachment_uploads {
+                intents |= GatewayIntents::MESSAGE_CONTENT;
+            }
+            let client = Client::builder(&self.config.token, intents)
                 .event_handler(Handler {
                     is_loop_running: AtomicBool::new(false),
                 })
@@ -63,6 +155,7 @@ impl IntoFuture for ServiceBuilder {
                 client,
                 user_id,
                 channel_id,
+                config: self.config,
             })
         })
     }
@@ -75,6 +168,10 @@ struct Handler {
 #[async_trait]
 impl EventHandler for Handler {
     async fn ready(&self, ctx: Context, _data: Ready) {
+        if let Err(err) = register_commands(&ctx).await {
+            tracing::error!("Discord command registration error: {:?}", err);
+        }
+
         let ctx = Arc::new(ctx);
         if self.is_loop_running.load(Ordering::Relaxed) {
             return;
@@ -82,19 +179,43 @@ impl EventHandler for Handler {
 
         let ctx = Arc::clone(&ctx);
         tokio::spawn(async move {
-            if let Err(err) = run(&ctx).await {
-                tracing::error!("Discord run error: {:?}", err);
+            loop {
+                if let Err(err) = run(&ctx).await {
+                    tracing::error!("Discord run error: {:?}", err);
+                }
+                time::sleep(RESTART_DELAY).await;
             }
         });
 
         self.is_loop_running.swap(true, Ordering::Relaxed);
     }
+
+    async fn message(&self, ctx: Context, message: Message) {
+        if let Err(err) = upload_attachments(&ctx, &message).await {
+            tracing::error!("Discord message error: {:?}", err);
+        }
+    }
+
+    async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
+        let result = match &interaction {
+            Interaction::Command(command) => commands::run(&ctx, command).await,
+            Interaction::Autocomplete(interaction) => {
+                commands::autocomplete(&ctx, interaction).await
+            }
+            Interaction::Component(interaction) => components::handle(&ctx, interaction).await,
+            _ => Ok(()),
+        };
+        if let Err(err) = result {
+            tracing::error!("Discord interaction error: {:?}", err);
+        }
+    }
 }
 
 pub struct Service {
     client: Client,
     user_id: UserId,
     channel_id: ChannelId,
+    config: Config,
 }
 
 impl Service {
@@ -105,22 +226,82 @@ impl Service {
     pub async fn start(
         mut self,
         status_rx: watch::Receiver<moonraker::Status>,
-        notification_rx: mpsc::Receiver<moonraker::Notification>,
+        event_rx: mpsc::Receiver<moonraker::Event>,
+        console_tx: broadcast::Sender<String>,
+        moonraker: Arc<moonraker::Client>,
     ) -> Result<()> {
         {
             let mut data = self.client.data.write().await;
 
             data.insert::<OwnerId>(Arc::new(self.user_id));
             data.insert::<PrintsChannel>(Arc::new(self.channel_id));
+            data.insert::<DiscordConfig>(Arc::new(self.config));
 
+            data.insert::<CurrentStatus>(status_rx.clone());
             data.insert::<StatusChannel>(Arc::new(Mutex::new(status_rx)));
-            data.insert::<NotificationChannel>(Arc::new(Mutex::new(notification_rx)));
+            data.insert::<EventChannel>(Arc::new(Mutex::new(event_rx)));
+            data.insert::<Console>(console_tx);
+            data.insert::<Moonraker>(moonraker);
+            data.insert::<PendingPowerOff>(Arc::new(AtomicU64::new(0)));
         }
         self.client.start().await?;
         Ok(())
     }
 }
 
+async fn register_commands(ctx: &Context) -> Result<()> {
+    let channel_id = {
+        let data_read = ctx.data.read().await;
+        data_read
+            .get::<PrintsChannel>()
+            .unwrap()
+            .as_ref()
+            .to_owned()
+    };
+    if let Channel::Guild(channel) = ctx.http.get_channel(channel_id).await? {
+        channel.guild_id.set_commands(ctx, commands::all()).await?;
+    }
+    Ok(())
+}
+
+/// Uploads the G-code files the owner attaches to messages in the prints channel.
+async fn upload_attachments(ctx: &Context, message: &Message) -> Result<()> {
+    let channel_id = {
+        let data_read = ctx.data.read().await;
+        data_read
+            .get::<PrintsChannel>()
+            .unwrap()
+            .as_ref()
+            .to_owned()
+    };
+    if message.author.bot
+        || message.channel_id != channel_id
+        || !auth::is_authorized(ctx, message.author.id, None).await
+    {
+        return Ok(());
+    }
+
+    for attachment in message.attachments.iter().filter(|a| upload::is_gcode(a)) {
+        let reply = match upload::upload_gcode(ctx, attachment).await {
+            Ok((content, components)) => {
+                CreateMessage::new().content(content).components(components)
+            }
+            Err(err) => {
+                tracing::error!("error uploading {:?}: {:?}", attachment.filename, err);
+                CreateMessage::new()
+                    .content(format!("Failed to upload {}: {}", attachment.filename, err))
+            }
+        };
+        retry(|| {
+            message
+                .channel_id
+                .send_message(ctx, reply.clone().reference_message(message))
+        })
+        .await?;
+    }
+    Ok(())
+}
+
 async fn run(ctx: &Context) -> Result<()> {
     let user_id = {
         let data_read = ctx.data.read().await;
@@ -134,61 +315,434 @@ async fn run(ctx: &Context) -> Result<()> {
             .as_ref()
             .to_owned()
     };
-    let user = ctx.http.get_user(user_id).await?;
-    let channel = ctx.http.get_channel(channel_id).await?;
+    let config = {
+        let data_read = ctx.data.read().await;
+        data_read.get::<DiscordConfig>().unwrap().clone()
+    };
 
     let status_rx = {
         let data_read = ctx.data.read().await;
         data_read.get::<StatusChannel>().unwrap().clone()
     };
 
-    let notification_rx = {
+    let event_rx = {
+        let data_read = ctx.data.read().await;
+        data_read.get::<EventChannel>().unwrap().clone()
+    };
+    let pending_power_off = {
         let data_read = ctx.data.read().await;
-        data_read.get::<NotificationChannel>().unwrap().clone()
+        data_read.get::<PendingPowerOff>().unwrap().clone()
     };
 
     let status = status_rx.lock().await.borrow_and_update().clone();
     set_presence(ctx, &status.state);
 
-    let mut current_file_name = String::default();
-    // TODO: make these Option
-    let mut thread = GuildChannel::default();
-    let mut message = Message::default();
+    let mut runner = Runner {
+        ctx,
+        power_off: config
+            .auto_power_off
+            .clone()
+            .map(|config| PowerOff::new(config, pending_power_off)),
+        config,
+        user_id,
+        channel_id,
+        state: status.state,
+        job: None,
+        thread_retry_at: None,
+    };
 
     loop {
         let mut status_rx_lock = status_rx.lock().await;
-        let mut notification_rx_lock = notification_rx.lock().await;
-        // TODO: handle errors
+        let mut event_rx_lock = event_rx.lock().await;
+        let flush_deadline = runner.flush_deadline();
+        let power_off_deadline = runner.power_off.as_ref().and_then(PowerOff::deadline);
         select! {
             Ok(()) = status_rx_lock.changed() => {
                 let status = status_rx_lock.borrow_and_update().clone();
-                set_presence(ctx, &status.state);
-
-                if let Channel::Guild(channel) = channel.clone() {
-                    if let Some(job) = status.clone().printer.and_then(|printer| printer.job) {
-                        if job.file_name != current_file_name {
-                            current_file_name = job.file_name.clone();
-                            thread = channel.create_thread(ctx, CreateThread::new(current_file_name.clone()).kind(ChannelType::PublicThread)).await?;
-                            message = thread.send_message(ctx, JobStatusMessage::from((status.state, job)).into()).await?;
-                        } else {
-                            message.edit(ctx, JobStatusMessage::from((status.state, job)).into()).await?;
-                        }
-                    }
+                if let Err(err) = runner.update_status(status).await {
+                    tracing::error!("error updating job status: {:?}", err);
                 }
             },
-                Some(notification) = notification_rx_lock.recv() => {
-                let mut message_builder = CreateMessage::new()
-                    .content(format!("{}\n{}", Mention::from(user.id),notification.message))
-                    .allowed_mentions(CreateAllowedMentions::new().users(vec![user.id]));
-                if let Some(image) =  notification.image {
-                    message_builder = message_builder.add_file(CreateAttachment::file(&image.into(), "image.png").await?);
-                };
-                thread.send_message(ctx, message_builder).await?;
+            Some(event) = event_rx_lock.recv() => {
+                let status = status_rx_lock.borrow().clone();
+                if let Err(err) = runner.handle_event(status, event).await {
+                    tracing::error!("error handling event: {:?}", err);
+                }
             },
+            _ = time::sleep_until(flush_deadline.unwrap_or_else(Instant::now)), if flush_deadline.is_some() => {
+                if let Err(err) = runner.flush().await {
+                    tracing::error!("error flushing job status: {:?}", err);
+                }
+            },
+            _ = time::sleep_until(power_off_deadline.unwrap_or_else(Instant::now)), if power_off_deadline.is_some() => {
+                let status = status_rx_lock.borrow().clone();
+                if let Err(err) = runner.power_off(status).await {
+                    tracing::error!("error switching off: {:?}", err);
+                }
+            },
+            else => return Err(anyhow!("moonraker channels closed")),
+        }
+    }
+}
+
+struct Runner<'a> {
+    ctx: &'a Context,
+    config: Arc<Config>,
+    user_id: UserId,
+    channel_id: ChannelId,
+    state: State,
+    job: Option<JobThread>,
+    /// When to try again after creating the job thread failed.
+    thread_retry_at: Option<Instant>,
+    power_off: Option<PowerOff>,
+}
+
+impl Runner<'_> {
+    async fn update_status(&mut self, status: moonraker::Status) -> Result<()> {
+        let state_changed = self.state != status.state;
+        self.state = status.state.clone();
+        if state_changed {
+            set_presence(self.ctx, &status.state);
+            self.schedule_power_off(&status.state).await?;
         }
+        let Some(info) = status.printer.and_then(|printer| printer.job) else {
+            return Ok(());
+        };
+        let job = match self.job.take() {
+            Some(job) if job.file_name == info.file_name => job,
+            _ => {
+                if self
+                    .thread_retry_at
+                    .is_some_and(|retry_at| Instant::now() < retry_at)
+                {
+                    return Ok(());
+                }
+                match JobThread::create(
+                    self.ctx,
+                    self.channel_id,
+                    info.file_name.clone(),
+                    Duration::from_secs(self.config.edit_interval),
+                )
+                .await
+                {
+                    Ok(job) => {
+                        self.thread_retry_at = None;
+                        job
+                    }
+                    Err(err) => {
+                        // A missing permission would otherwise fail on every status update
+                        self.thread_retry_at = Some(Instant::now() + THREAD_RETRY_DELAY);
+                        return Err(err.into());
+                    }
+                }
+            }
+        };
+        let job = self.job.insert(job);
+
+        if let Err(err) = job
+            .update(
+                self.ctx,
+                JobStatusMessage::from((status.state.clone(), info, status.queue)),
+                state_changed,
+            )
+            .await
+        {
+            if is_unknown_channel(&err) {
+                tracing::warn!("job thread was deleted, creating a new one");
+                self.job = None;
+            }
+            return Err(err.into());
+        }
+
+        if status.state == State::Complete && !job.timelapse.is_empty() {
+            let ctx = self.ctx.clone();
+            let thread_id = job.thread_id;
+            let timelapse = std::mem::take(&mut job.timelapse);
+            let size_limit = self.config.attachment_limit;
+            tokio::spawn(async move {
+                if let Err(err) = upload_timelapse(&ctx, thread_id, timelapse, size_limit).await {
+                    tracing::error!("error uploading timelapse: {:?}", err);
+                }
+            });
+        }
+        Ok(())
+    }
+
+    async fn handle_event(
+        &mut self,
+        status: moonraker::Status,
+        event: moonraker::Event,
+    ) -> Result<()> {
+        match event {
+            moonraker::Event::Notification(notification) => {
+                let mut content =
+                    format!("{}\n{}", Mention::from(self.user_id), notification.message);
+                for webcam in &notification.unavailable_webcams {
+                    content.push_str(&format!("\n*Image from {} unavailable*", webcam));
+                }
+                let mut message = CreateMessage::new()
+                    .content(content)
+                    .allowed_mentions(CreateAllowedMentions::new().users(vec![self.user_id]));
+                for snapshot in notification.images {
+                    let file_name = snapshot.image.file_name(&snapshot.webcam);
+                    message =
+                        message.add_file(CreateAttachment::bytes(snapshot.image.data, file_name));
+                }
+                self.send(message).await?;
+            }
+            moonraker::Event::Snapshot(snapshot) => {
+                if let Some(job) = &mut self.job {
+                    if self.config.timelapse {
+                        job.timelapse.push(snapshot.image.clone());
+                    }
+                }
+                match self.config.snapshot_mode {
+                    SnapshotMode::Thread => {
+                        let file_name = snapshot.image.file_name(&snapshot.webcam);
+                        let attachment = CreateAttachment::bytes(snapshot.image.data, file_name);
+                        self.send(CreateMessage::new().add_file(attachment)).await?;
+                    }
+                    SnapshotMode::Embed => {
+                        let info = status.printer.and_then(|printer| printer.job);
+                        if let (Some(job), Some(info)) = (&mut self.job, info) {
+                            job.update_snapshot(
+                                self.ctx,
+                                JobStatusMessage::from((status.state, info, status.queue)),
+                                &snapshot.image,
+                            )
+                            .await?;
+                        }
+                    }
+                }
+            }
+            moonraker::Event::TimelapseRender(render) => {
+                let ctx = self.ctx.clone();
+                let channel_id = self.target_channel();
+                let size_limit = self.config.attachment_limit;
+                tokio::spawn(async move {
+                    if let Err(err) =
+                        upload_timelapse_render(&ctx, channel_id, render, size_limit).await
+                    {
+                        tracing::error!("error uploading timelapse render: {:?}", err);
+                    }
+                });
+            }
+            moonraker::Event::UpdatesAvailable(updates) => {
+                let content = updates
+                    .iter()
+                    .map(|update| format!("- **{}**: {}", update.name, update.description))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                let message = CreateMessage::new()
+                    .content(format!("Updates available\n{}", content))
+                    .components(components::update_buttons(&updates));
+                self.send_to_prints_channel(message).await?;
+            }
+            moonraker::Event::Announcement(announcement) => {
+                let embed = CreateEmbed::new()
+                    .title(announcement.title)
+                    .url(announcement.url)
+                    .description(announcement.description)
+                    .field("Priority", announcement.priority, true);
+                let message = CreateMessage::new()
+                    .content("Moonraker announcement")
+                    .embed(embed)
+                    .components(
+                        components::announcement_dismiss_button(&announcement.entry_id)
+                            .into_iter()
+                            .collect(),
+                    );
+                self.send_to_prints_channel(message).await?;
+            }
+            moonraker::Event::ConsoleError(error) => {
+                let context = error
+                    .context
+                    .iter()
+                    .enumerate()
+                    .map(|(index, line)| {
+                        let marker = if index == error.error_index { ">" } else { " " };
+                        format!("{} {}", marker, line)
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                let message = CreateMessage::new()
+                    .content(format!(
+                        "{}\n**Printer error:** {}\n```\n{}\n```",
+                        Mention::from(self.user_id),
+                        error.line.trim_start_matches("!!").trim(),
+                        context.replace("```", "'''"),
+                    ))
+                    .allowed_mentions(CreateAllowedMentions::new().users(vec![self.user_id]));
+                self.send(message).await?;
+            }
+            moonraker::Event::HostAlert(alert) => {
+                let message = CreateMessage::new()
+                    .content(format!(
+                        "{}\n**Host:** {}",
+                        Mention::from(self.user_id),
+                        alert
+                    ))
+                    .allowed_mentions(CreateAllowedMentions::new().users(vec![self.user_id]));
+                self.send_to_prints_channel(message).await?;
+            }
+        }
+        Ok(())
+    }
+
+    fn flush_deadline(&self) -> Option<Instant> {
+        self.job.as_ref()?.pending_deadline()
+    }
+
+    async fn flush(&mut self) -> Result<()> {
+        if let Some(job) = &mut self.job {
+            job.flush(self.ctx).await?;
+        }
+        Ok(())
+    }
+
+    /// Schedules the power-off when a job completed, and cancels it when the
+    /// printer is used again.
+    async fn schedule_power_off(&mut self, state: &State) -> Result<()> {
+        let Some(power_off) = &mut self.power_off else {
+            return Ok(());
+        };
+        match state {
+            State::Complete => {}
+            State::Printing | State::Paused => {
+                power_off.cancel();
+                return Ok(());
+            }
+            _ => return Ok(()),
+        }
+
+        let id = power_off.schedule();
+        let content = format!(
+            "{} will be switched off in {}, once the extruder is below {}°C",
+            power_off.config.device,
+            power_off::format_delay(power_off.config.delay),
+            power_off.config.max_temperature
+        );
+        self.send(
+            CreateMessage::new()
+                .content(content)
+                .components(vec![components::power_off_keep_on_button(id)]),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Switches the device off if the extruder has cooled down, otherwise
+    /// checks again later.
+    async fn power_off(&mut self, status: moonraker::Status) -> Result<()> {
+        let Some(power_off) = &mut self.power_off else {
+            return Ok(());
+        };
+        // "Keep on" may have been pressed while the deadline was awaited
+        if power_off.deadline().is_none() {
+            tracing::debug!("power-off was cancelled");
+            return Ok(());
+        }
+        let extruder_temperature = status
+            .printer
+            .and_then(|printer| printer.extruder)
+            .map(|extruder| extruder.current);
+        if !power_off.is_cool(extruder_temperature) {
+            tracing::debug!(
+                "waiting for the extruder to cool down: {:?}",
+                extruder_temperature
+            );
+            power_off.postpone();
+            return Ok(());
+        }
+        power_off.cancel();
+
+        let device = power_off.config.device.clone();
+        let client = {
+            let data_read = self.ctx.data.read().await;
+            data_read.get::<Moonraker>().unwrap().clone()
+        };
+        client.set_power_device(&device, "off").await?;
+        tracing::info!("switched {:?} off after the job completed", device);
+        self.send(CreateMessage::new().content(format!("Switched {} off", device)))
+            .await?;
+        Ok(())
+    }
+
+    /// The job thread if there is one, otherwise the prints channel.
+    fn target_channel(&self) -> ChannelId {
+        self.job
+            .as_ref()
+            .map(|job| job.thread_id)
+            .unwrap_or(self.channel_id)
+    }
+
+    /// Sends a message to the job thread, falling back to the prints channel.
+    async fn send(&mut self, message: CreateMessage) -> serenity::Result<()> {
+        if let Some(job) = &self.job {
+            match job.send(self.ctx, message.clone()).await {
+                Err(err) if is_unknown_channel(&err) => {
+                    tracing::warn!("job thread was deleted, sending to the prints channel");
+                    self.job = None;
+                }
+                result => return result,
+            }
+        }
+        self.send_to_prints_channel(message).await
+    }
+
+    /// Sends a message that is not about the current job to the prints channel.
+    async fn send_to_prints_channel(&self, message: CreateMessage) -> serenity::Result<()> {
+        let channel_id = self.channel_id;
+        retry(|| channel_id.send_message(self.ctx, message.clone())).await?;
+        Ok(())
     }
 }
 
+async fn upload_timelapse(
+    ctx: &Context,
+    thread_id: ChannelId,
+    timelapse: Timelapse,
+    size_limit: u64,
+) -> Result<()> {
+    match timelapse.render(size_limit).await? {
+        Some(gif) => {
+            let message =
+                CreateMessage::new().add_file(CreateAttachment::bytes(gif, TIMELAPSE_FILE_NAME));
+            retry(|| thread_id.send_message(ctx, message.clone())).await?;
+        }
+        None => tracing::warn!("timelapse does not fit in {} bytes", size_limit),
+    }
+    Ok(())
+}
+
+async fn upload_timelapse_render(
+    ctx: &Context,
+    channel_id: ChannelId,
+    render: moonraker::TimelapseRender,
+    size_limit: u64,
+) -> Result<()> {
+    let client = {
+        let data_read = ctx.data.read().await;
+        data_read.get::<Moonraker>().unwrap().clone()
+    };
+    let message = match client
+        .download_file_limited(TIMELAPSE_ROOT, &render.file_name, size_limit)
+        .await
+    {
+        Ok(Some(video)) => {
+            CreateMessage::new().add_file(CreateAttachment::bytes(video, render.file_name))
+        }
+        Ok(None) => CreateMessage::new()
+            .content(format!("Timelapse is too large to upload: {}", render.url)),
+        Err(err) => {
+            tracing::error!("error downloading timelapse render: {:?}", err);
+            CreateMessage::new().content(format!("Timelapse is ready: {}", render.url))
+        }
+    };
+    retry(|| channel_id.send_message(ctx, message.clone())).await?;
+    Ok(())
+}
+
 fn set_presence(ctx: &Context, state: &State) {
     match state {
         State::Disconnected => {
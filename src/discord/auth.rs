@@ -0,0 +1,43 @@
+use serenity::all::{
+    Context, CreateInteractionResponse, CreateInteractionResponseMessage, Member, UserId,
+};
+
+use super::typemap::{DiscordConfig, OwnerId};
+
+const DENIED_MESSAGE: &str = "You are not allowed to control the printer";
+
+/// The ephemeral reply to users that may not control the printer.
+pub fn denied_response() -> CreateInteractionResponse {
+    CreateInteractionResponse::Message(
+        CreateInteractionResponseMessage::new()
+            .content(DENIED_MESSAGE)
+            .ephemeral(true),
+    )
+}
+
+/// Whether the user is the printer owner or has one of the admin roles.
+pub async fn is_authorized(ctx: &Context, user_id: UserId, member: Option<&Member>) -> bool {
+    if is_owner(ctx, user_id).await {
+        return true;
+    }
+
+    let data_read = ctx.data.read().await;
+    let admin_roles = data_read
+        .get::<DiscordConfig>()
+        .map(|config| config.admin_roles.as_slice())
+        .unwrap_or_default();
+    member.is_some_and(|member| {
+        member
+            .roles
+            .iter()
+            .any(|role| admin_roles.contains(&role.get()))
+    })
+}
+
+/// Whether the user is the printer owner, for actions admins may not take.
+pub async fn is_owner(ctx: &Context, user_id: UserId) -> bool {
+    let data_read = ctx.data.read().await;
+    data_read
+        .get::<OwnerId>()
+        .is_some_and(|owner_id| **owner_id == user_id)
+}
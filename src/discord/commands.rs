@@ -0,0 +1,48 @@
+mod gcode;
+mod macros;
+mod power;
+mod print;
+mod queue;
+mod status;
+mod upload;
+mod webcam;
+
+use anyhow::{anyhow, Result};
+use serenity::all::{CommandInteraction, Context, CreateCommand};
+
+pub fn all() -> Vec<CreateCommand> {
+    vec![
+        gcode::register(),
+        macros::register(),
+        power::register(),
+        print::register(),
+        queue::register(),
+        status::register(),
+        upload::register(),
+        webcam::register(),
+    ]
+}
+
+pub async fn run(ctx: &Context, command: &CommandInteraction) -> Result<()> {
+    match command.data.name.as_str() {
+        gcode::NAME => gcode::run(ctx, command).await,
+        macros::NAME => macros::run(ctx, command).await,
+        power::NAME => power::run(ctx, command).await,
+        print::NAME => print::run(ctx, command).await,
+        queue::NAME => queue::run(ctx, command).await,
+        status::NAME => status::run(ctx, command).await,
+        upload::NAME => upload::run(ctx, command).await,
+        webcam::NAME => webcam::run(ctx, command).await,
+        name => Err(anyhow!("unknown command: {:?}", name)),
+    }
+}
+
+pub async fn autocomplete(ctx: &Context, interaction: &CommandInteraction) -> Result<()> {
+    match interaction.data.name.as_str() {
+        power::NAME => power::autocomplete(ctx, interaction).await,
+        print::NAME => print::autocomplete(ctx, interaction).await,
+        queue::NAME => queue::autocomplete(ctx, interaction).await,
+        webcam::NAME => webcam::autocomplete(ctx, interaction).await,
+        name => Err(anyhow!("unknown command: {:?}", name)),
+    }
+}
@@ -0,0 +1,44 @@
+use anyhow::Result;
+use serenity::all::{
+    CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption,
+    EditInteractionResponse, ResolvedValue,
+};
+
+use crate::discord::{auth, console};
+
+pub const NAME: &str = "gcode";
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new(NAME)
+        .description("Run G-code on the printer")
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "script", "G-code to run")
+                .required(true),
+        )
+}
+
+pub async fn run(ctx: &Context, command: &CommandInteraction) -> Result<()> {
+    if !auth::is_authorized(ctx, command.user.id, command.member.as_deref()).await {
+        command
+            .create_response(ctx, auth::denied_response())
+            .await?;
+        return Ok(());
+    }
+
+    let script = command
+        .data
+        .options()
+        .into_iter()
+        .find_map(|option| match option.value {
+            ResolvedValue::String(script) if option.name == "script" => Some(script),
+            _ => None,
+        })
+        .unwrap_or_default();
+    command.defer(ctx).await?;
+
+    let content = console::run_gcode(ctx, script).await;
+    command
+        .edit_response(ctx, EditInteractionResponse::new().content(content))
+        .await?;
+    Ok(())
+}
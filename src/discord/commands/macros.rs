@@ -0,0 +1,34 @@
+use anyhow::Result;
+use serenity::all::{
+    CommandInteraction, Context, CreateCommand, CreateInteractionResponse,
+    CreateInteractionResponseMessage,
+};
+
+use crate::discord::{components, typemap::DiscordConfig};
+
+pub const NAME: &str = "macros";
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new(NAME).description("Show the configured macro buttons")
+}
+
+pub async fn run(ctx: &Context, command: &CommandInteraction) -> Result<()> {
+    let config = {
+        let data_read = ctx.data.read().await;
+        data_read.get::<DiscordConfig>().unwrap().clone()
+    };
+
+    let message = if config.macros.is_empty() {
+        CreateInteractionResponseMessage::new()
+            .content("No macros configured")
+            .ephemeral(true)
+    } else {
+        CreateInteractionResponseMessage::new()
+            .content("Macros")
+            .components(components::macro_buttons(&config.macros))
+    };
+    command
+        .create_response(ctx, CreateInteractionResponse::Message(message))
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,128 @@
+use anyhow::Result;
+use serenity::all::{
+    AutocompleteChoice, CommandInteraction, CommandOptionType, Context, CreateAutocompleteResponse,
+    CreateCommand, CreateCommandOption, CreateInteractionResponse,
+    CreateInteractionResponseMessage, EditInteractionResponse, ResolvedValue,
+};
+
+use crate::{
+    discord::{
+        auth, components,
+        typemap::{CurrentStatus, Moonraker},
+    },
+    moonraker::PowerDevice,
+};
+
+pub const NAME: &str = "power";
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new(NAME)
+        .description("Switch power devices")
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "device", "Device to switch")
+                .set_autocomplete(true),
+        )
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "action", "Defaults to toggle")
+                .add_string_choice("On", "on")
+                .add_string_choice("Off", "off")
+                .add_string_choice("Toggle", "toggle"),
+        )
+}
+
+pub async fn run(ctx: &Context, command: &CommandInteraction) -> Result<()> {
+    if !auth::is_authorized(ctx, command.user.id, command.member.as_deref()).await {
+        command
+            .create_response(ctx, auth::denied_response())
+            .await?;
+        return Ok(());
+    }
+    let (client, devices) = {
+        let data_read = ctx.data.read().await;
+        let devices = data_read
+            .get::<CurrentStatus>()
+            .unwrap()
+            .borrow()
+            .power_devices
+            .clone();
+        (data_read.get::<Moonraker>().unwrap().clone(), devices)
+    };
+
+    let options = command.data.options();
+    let option = |name: &str| {
+        options.iter().find_map(|option| match option.value {
+            ResolvedValue::String(value) if option.name == name => Some(value),
+            _ => None,
+        })
+    };
+    let Some(device) = option("device") else {
+        let message = if devices.is_empty() {
+            CreateInteractionResponseMessage::new().content("No power devices configured")
+        } else {
+            CreateInteractionResponseMessage::new()
+                .content("Power devices")
+                .components(components::power_buttons(&devices))
+        };
+        command
+            .create_response(ctx, CreateInteractionResponse::Message(message))
+            .await?;
+        return Ok(());
+    };
+    let action = option("action").unwrap_or("toggle");
+    command.defer(ctx).await?;
+
+    let content = match client.set_power_device(device, action).await {
+        Ok(status) => format!("{} is {}", device, status),
+        Err(err) => {
+            tracing::error!("error switching {:?} {}: {:?}", device, action, err);
+            format!("Failed to switch {} {}: {}", device, action, err)
+        }
+    };
+    command
+        .edit_response(ctx, EditInteractionResponse::new().content(content))
+        .await?;
+    Ok(())
+}
+
+pub async fn autocomplete(ctx: &Context, interaction: &CommandInteraction) -> Result<()> {
+    let devices = {
+        let data_read = ctx.data.read().await;
+        let devices = data_read
+            .get::<CurrentStatus>()
+            .unwrap()
+            .borrow()
+            .power_devices
+            .clone();
+        devices
+    };
+    let partial = interaction
+        .data
+        .autocomplete()
+        .map(|option| option.value.to_lowercase())
+        .unwrap_or_default();
+
+    let choices = devices
+        .into_iter()
+        .filter(|device| device.name.to_lowercase().contains(&partial))
+        .take(25)
+        .map(|device| AutocompleteChoice::new(device.name.clone(), device.name))
+        .collect();
+    interaction
+        .create_response(
+            ctx,
+            CreateInteractionResponse::Autocomplete(
+                CreateAutocompleteResponse::new().set_choices(choices),
+            ),
+        )
+        .await?;
+    Ok(())
+}
+
+/// One line per device with its status.
+pub fn format_devices(devices: &[PowerDevice]) -> String {
+    devices
+        .iter()
+        .map(|device| format!("{}: {}", device.name, device.status))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,173 @@
+use anyhow::Result;
+use serenity::all::{
+    AutocompleteChoice, CommandInteraction, CommandOptionType, Context, CreateAttachment,
+    CreateAutocompleteResponse, CreateCommand, CreateCommandOption, CreateEmbed,
+    CreateInteractionResponse, CreateInteractionResponseMessage, EditInteractionResponse,
+    ResolvedValue,
+};
+
+use crate::{
+    discord::{
+        auth, components,
+        typemap::{CurrentStatus, Moonraker},
+    },
+    moonraker::{Client, State},
+};
+
+pub const NAME: &str = "print";
+
+const ROOT: &str = "gcodes";
+const THUMBNAIL_NAME: &str = "thumbnail.png";
+const MAX_CHOICE_LENGTH: usize = 100;
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new(NAME)
+        .description("Start printing a G-code file")
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "file", "File to print")
+                .required(true)
+                .set_autocomplete(true),
+        )
+}
+
+pub async fn run(ctx: &Context, command: &CommandInteraction) -> Result<()> {
+    let reply = |content: &str| {
+        CreateInteractionResponse::Message(
+            CreateInteractionResponseMessage::new()
+                .content(content)
+                .ephemeral(true),
+        )
+    };
+    if !auth::is_authorized(ctx, command.user.id, command.member.as_deref()).await {
+        command
+            .create_response(ctx, auth::denied_response())
+            .await?;
+        return Ok(());
+    }
+    let (client, state) = {
+        let data_read = ctx.data.read().await;
+        let state = data_read
+            .get::<CurrentStatus>()
+            .unwrap()
+            .borrow()
+            .state
+            .clone();
+        (data_read.get::<Moonraker>().unwrap().clone(), state)
+    };
+    if matches!(state, State::Printing | State::Paused) {
+        command
+            .create_response(ctx, reply(components::PRINT_BUSY_MESSAGE))
+            .await?;
+        return Ok(());
+    }
+
+    let file_name = command
+        .data
+        .options()
+        .into_iter()
+        .find_map(|option| match option.value {
+            ResolvedValue::String(file_name) if option.name == "file" => Some(file_name),
+            _ => None,
+        })
+        .unwrap_or_default();
+    let Some(buttons) = components::print_confirm_buttons(file_name) else {
+        command
+            .create_response(ctx, reply("The file name is too long to confirm"))
+            .await?;
+        return Ok(());
+    };
+    command.defer_ephemeral(ctx).await?;
+
+    let metadata = match client.get_file_metadata(file_name).await {
+        Ok(metadata) => metadata,
+        Err(err) => {
+            tracing::error!("error getting metadata of {:?}: {:?}", file_name, err);
+            command
+                .edit_response(
+                    ctx,
+                    EditInteractionResponse::new().content(format!("Unknown file {}", file_name)),
+                )
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let mut embed = CreateEmbed::new().title(format!("Print {}?", file_name));
+    if let Some(estimated_time) = metadata.estimated_time {
+        embed = embed.field("Estimated time", format_duration(estimated_time), true);
+    }
+    let mut response = EditInteractionResponse::new().components(vec![buttons]);
+    let thumbnail = metadata
+        .thumbnails
+        .iter()
+        .max_by_key(|thumbnail| thumbnail.width * thumbnail.height);
+    if let Some(thumbnail) = thumbnail {
+        match download_thumbnail(&client, file_name, &thumbnail.relative_path).await {
+            Ok(data) => {
+                embed = embed.attachment(THUMBNAIL_NAME);
+                response = response.new_attachment(CreateAttachment::bytes(data, THUMBNAIL_NAME));
+            }
+            Err(err) => {
+                tracing::error!("error downloading thumbnail of {:?}: {:?}", file_name, err)
+            }
+        }
+    }
+    command.edit_response(ctx, response.embed(embed)).await?;
+    Ok(())
+}
+
+pub async fn autocomplete(ctx: &Context, interaction: &CommandInteraction) -> Result<()> {
+    let client = {
+        let data_read = ctx.data.read().await;
+        data_read.get::<Moonraker>().unwrap().clone()
+    };
+    let partial = interaction
+        .data
+        .autocomplete()
+        .map(|option| option.value.to_lowercase())
+        .unwrap_or_default();
+
+    let choices = file_choices(&client, &partial).await?;
+    interaction
+        .create_response(
+            ctx,
+            CreateInteractionResponse::Autocomplete(
+                CreateAutocompleteResponse::new().set_choices(choices),
+            ),
+        )
+        .await?;
+    Ok(())
+}
+
+/// The G-code files matching the partial input, most recently modified first.
+pub async fn file_choices(client: &Client, partial: &str) -> Result<Vec<AutocompleteChoice>> {
+    let mut files = client.list_files(ROOT).await?;
+    files.sort_by(|a, b| b.modified.total_cmp(&a.modified));
+    Ok(files
+        .into_iter()
+        .filter(|file| file.path.len() <= MAX_CHOICE_LENGTH)
+        .filter(|file| file.path.to_lowercase().contains(partial))
+        .take(25)
+        .map(|file| AutocompleteChoice::new(file.path.clone(), file.path))
+        .collect())
+}
+
+async fn download_thumbnail(
+    client: &Client,
+    file_name: &str,
+    relative_path: &str,
+) -> Result<Vec<u8>> {
+    let path = match file_name.rsplit_once('/') {
+        Some((directory, _)) => format!("{}/{}", directory, relative_path),
+        None => relative_path.to_string(),
+    };
+    client.download_file(ROOT, &path).await
+}
+
+fn format_duration(seconds: f64) -> String {
+    let minutes = (seconds / 60.0).round() as u64;
+    match (minutes / 60, minutes % 60) {
+        (0, minutes) => format!("{}m", minutes),
+        (hours, minutes) => format!("{}h {}m", hours, minutes),
+    }
+}
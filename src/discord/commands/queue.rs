@@ -0,0 +1,185 @@
+use anyhow::Result;
+use serenity::all::{
+    AutocompleteChoice, CommandInteraction, CommandOptionType, Context, CreateAutocompleteResponse,
+    CreateCommand, CreateCommandOption, CreateInteractionResponse,
+    CreateInteractionResponseMessage, EditInteractionResponse, ResolvedOption, ResolvedValue,
+};
+
+use super::print::file_choices;
+use crate::{
+    discord::{
+        auth,
+        typemap::{CurrentStatus, Moonraker},
+    },
+    moonraker::JobQueue,
+};
+
+pub const NAME: &str = "queue";
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new(NAME)
+        .description("Manage the job queue")
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "list",
+            "Show the queued jobs",
+        ))
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::SubCommand, "add", "Queue a file")
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::String, "file", "File to queue")
+                        .required(true)
+                        .set_autocomplete(true),
+                ),
+        )
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::SubCommand, "remove", "Remove a job")
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::String, "job", "Job to remove")
+                        .required(true)
+                        .set_autocomplete(true),
+                ),
+        )
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "start",
+            "Start printing the queued jobs",
+        ))
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "pause",
+            "Stop starting queued jobs",
+        ))
+}
+
+pub async fn run(ctx: &Context, command: &CommandInteraction) -> Result<()> {
+    let options = command.data.options();
+    let Some(ResolvedOption {
+        name: subcommand,
+        value: ResolvedValue::SubCommand(options),
+        ..
+    }) = options.first()
+    else {
+        return Ok(());
+    };
+
+    if *subcommand == "list" {
+        let queue = {
+            let data_read = ctx.data.read().await;
+            let queue = data_read
+                .get::<CurrentStatus>()
+                .unwrap()
+                .borrow()
+                .queue
+                .clone();
+            queue
+        };
+        command
+            .create_response(
+                ctx,
+                CreateInteractionResponse::Message(
+                    CreateInteractionResponseMessage::new().content(format_queue(&queue)),
+                ),
+            )
+            .await?;
+        return Ok(());
+    }
+
+    if !auth::is_authorized(ctx, command.user.id, command.member.as_deref()).await {
+        command
+            .create_response(ctx, auth::denied_response())
+            .await?;
+        return Ok(());
+    }
+    let client = {
+        let data_read = ctx.data.read().await;
+        data_read.get::<Moonraker>().unwrap().clone()
+    };
+    let argument = options
+        .iter()
+        .find_map(|option| match option.value {
+            ResolvedValue::String(value) => Some(value),
+            _ => None,
+        })
+        .unwrap_or_default();
+    command.defer(ctx).await?;
+
+    let result = match *subcommand {
+        "add" => client.enqueue_job(argument).await,
+        "remove" => client.remove_job(argument).await,
+        "start" => client.start_job_queue().await,
+        "pause" => client.pause_job_queue().await,
+        _ => return Ok(()),
+    };
+    let content = match result {
+        Ok(queue) => format_queue(&queue.into()),
+        Err(err) => {
+            tracing::error!("error running queue {}: {:?}", subcommand, err);
+            format!("Failed to {} the queue: {}", subcommand, err)
+        }
+    };
+    command
+        .edit_response(ctx, EditInteractionResponse::new().content(content))
+        .await?;
+    Ok(())
+}
+
+pub async fn autocomplete(ctx: &Context, interaction: &CommandInteraction) -> Result<()> {
+    let Some(option) = interaction.data.autocomplete() else {
+        return Ok(());
+    };
+    let partial = option.value.to_lowercase();
+
+    let choices = match option.name {
+        "file" => {
+            let client = {
+                let data_read = ctx.data.read().await;
+                data_read.get::<Moonraker>().unwrap().clone()
+            };
+            file_choices(&client, &partial).await?
+        }
+        _ => {
+            let queue = {
+                let data_read = ctx.data.read().await;
+                let queue = data_read
+                    .get::<CurrentStatus>()
+                    .unwrap()
+                    .borrow()
+                    .queue
+                    .clone();
+                queue
+            };
+            queue
+                .jobs
+                .into_iter()
+                .filter(|job| job.file_name.to_lowercase().contains(&partial))
+                .take(25)
+                .map(|job| AutocompleteChoice::new(job.file_name, job.id))
+                .collect()
+        }
+    };
+    interaction
+        .create_response(
+            ctx,
+            CreateInteractionResponse::Autocomplete(
+                CreateAutocompleteResponse::new().set_choices(choices),
+            ),
+        )
+        .await?;
+    Ok(())
+}
+
+fn format_queue(queue: &JobQueue) -> String {
+    if queue.jobs.is_empty() {
+        return format!("The job queue is empty ({})", queue.state);
+    }
+
+    let jobs = queue
+        .jobs
+        .iter()
+        .enumerate()
+        .map(|(index, job)| format!("{}. {}", index + 1, job.file_name))
+        .collect::<Vec<_>>()
+        .join("\n");
+    format!("**Job queue** ({})\n{}", queue.state, jobs)
+}
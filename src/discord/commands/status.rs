@@ -0,0 +1,94 @@
+use anyhow::Result;
+use serenity::all::{
+    CommandInteraction, Context, CreateCommand, CreateEmbed, CreateInteractionResponse,
+    CreateInteractionResponseMessage,
+};
+
+use super::power::format_devices;
+use crate::{discord::typemap::CurrentStatus, moonraker::HostStatus};
+
+pub const NAME: &str = "status";
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new(NAME).description("Show the printer status")
+}
+
+pub async fn run(ctx: &Context, command: &CommandInteraction) -> Result<()> {
+    let status = {
+        let data_read = ctx.data.read().await;
+        let status = data_read.get::<CurrentStatus>().unwrap().borrow().clone();
+        status
+    };
+
+    let mut embed =
+        CreateEmbed::new()
+            .title("Printer Status")
+            .field("State", status.state.to_string(), true);
+    let printer = status.printer.unwrap_or_default();
+    for (name, temperature) in [("Extruder", printer.extruder), ("Bed", printer.heater_bed)] {
+        if let Some(temperature) = temperature {
+            embed = embed.field(
+                name,
+                format!("{:.1} / {:.0}°C", temperature.current, temperature.target),
+                true,
+            );
+        }
+    }
+    if let Some(job) = printer.job {
+        embed = embed.field(
+            "Job",
+            format!(
+                "{}\nLayer {} / {}",
+                job.file_name, job.current_layer, job.total_layer
+            ),
+            false,
+        );
+    }
+    if !status.queue.jobs.is_empty() {
+        embed = embed.field("Queued Jobs", status.queue.jobs.len().to_string(), true);
+    }
+    if !status.power_devices.is_empty() {
+        embed = embed.field("Power", format_devices(&status.power_devices), false);
+    }
+    if let Some(host) = format_host(&status.host) {
+        embed = embed.field("Host", host, false);
+    }
+    command
+        .create_response(
+            ctx,
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new().embed(embed),
+            ),
+        )
+        .await?;
+    Ok(())
+}
+
+fn format_host(host: &HostStatus) -> Option<String> {
+    let mut lines = Vec::new();
+    if let Some(temperature) = host.cpu_temperature {
+        lines.push(format!("CPU {:.1}°C", temperature));
+    }
+    if let (Some(available), Some(total)) = (host.memory_available, host.memory_total) {
+        lines.push(format!(
+            "Memory {} / {} MiB free",
+            available / 1024,
+            total / 1024
+        ));
+    }
+    if let (Some(free), Some(total)) = (host.disk_free, host.disk_total) {
+        lines.push(format!(
+            "Disk {} / {} MiB free",
+            free / 1024 / 1024,
+            total / 1024 / 1024
+        ));
+    }
+    let throttling = host.current_throttling().collect::<Vec<_>>();
+    if !throttling.is_empty() {
+        lines.push(format!("Throttled: {}", throttling.join(", ")));
+    }
+    if host.websocket_connections > 0 {
+        lines.push(format!("{} clients connected", host.websocket_connections));
+    }
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
@@ -0,0 +1,63 @@
+use anyhow::Result;
+use serenity::all::{
+    CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption,
+    CreateInteractionResponse, CreateInteractionResponseMessage, EditInteractionResponse,
+    ResolvedValue,
+};
+
+use crate::discord::{auth, upload};
+
+pub const NAME: &str = "upload";
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new(NAME)
+        .description("Upload a G-code file to the printer")
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::Attachment, "file", "G-code file")
+                .required(true),
+        )
+}
+
+pub async fn run(ctx: &Context, command: &CommandInteraction) -> Result<()> {
+    let reply = |content: &str| {
+        CreateInteractionResponse::Message(
+            CreateInteractionResponseMessage::new()
+                .content(content)
+                .ephemeral(true),
+        )
+    };
+    if !auth::is_authorized(ctx, command.user.id, command.member.as_deref()).await {
+        command
+            .create_response(ctx, auth::denied_response())
+            .await?;
+        return Ok(());
+    }
+    let attachment = command
+        .data
+        .options()
+        .into_iter()
+        .find_map(|option| match option.value {
+            ResolvedValue::Attachment(attachment) if option.name == "file" => Some(attachment),
+            _ => None,
+        });
+    let Some(attachment) = attachment.filter(|attachment| upload::is_gcode(attachment)) else {
+        command
+            .create_response(ctx, reply("Only .gcode files can be uploaded"))
+            .await?;
+        return Ok(());
+    };
+    command.defer(ctx).await?;
+
+    let response = match upload::upload_gcode(ctx, attachment).await {
+        Ok((content, components)) => EditInteractionResponse::new()
+            .content(content)
+            .components(components),
+        Err(err) => {
+            tracing::error!("error uploading {:?}: {:?}", attachment.filename, err);
+            EditInteractionResponse::new()
+                .content(format!("Failed to upload {}: {}", attachment.filename, err))
+        }
+    };
+    command.edit_response(ctx, response).await?;
+    Ok(())
+}
@@ -0,0 +1,94 @@
+use anyhow::Result;
+use serenity::all::{
+    AutocompleteChoice, CommandInteraction, CommandOptionType, Context, CreateAttachment,
+    CreateAutocompleteResponse, CreateCommand, CreateCommandOption, CreateInteractionResponse,
+    EditInteractionResponse, ResolvedValue,
+};
+
+use crate::{
+    discord::typemap::Moonraker,
+    moonraker::{webcam, Client},
+};
+
+pub const NAME: &str = "webcam";
+
+pub fn register() -> CreateCommand {
+    CreateCommand::new(NAME)
+        .description("Take a webcam snapshot")
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "name", "Webcam to use")
+                .set_autocomplete(true),
+        )
+}
+
+pub async fn run(ctx: &Context, command: &CommandInteraction) -> Result<()> {
+    let client = {
+        let data_read = ctx.data.read().await;
+        data_read.get::<Moonraker>().unwrap().clone()
+    };
+    command.defer(ctx).await?;
+
+    let name = command
+        .data
+        .options()
+        .into_iter()
+        .find_map(|option| match option.value {
+            ResolvedValue::String(name) if option.name == "name" => Some(name),
+            _ => None,
+        });
+    let response = match take_snapshot(&client, name).await {
+        Ok(Some((webcam, image))) => {
+            let file_name = image.file_name(&webcam);
+            EditInteractionResponse::new()
+                .new_attachment(CreateAttachment::bytes(image.data, file_name))
+        }
+        Ok(None) => EditInteractionResponse::new().content("No webcams configured"),
+        Err(err) => {
+            tracing::error!("error taking snapshot with {:?}: {:?}", name, err);
+            EditInteractionResponse::new().content("Failed to take a snapshot")
+        }
+    };
+    command.edit_response(ctx, response).await?;
+    Ok(())
+}
+
+pub async fn autocomplete(ctx: &Context, interaction: &CommandInteraction) -> Result<()> {
+    let client = {
+        let data_read = ctx.data.read().await;
+        data_read.get::<Moonraker>().unwrap().clone()
+    };
+    let partial = interaction
+        .data
+        .autocomplete()
+        .map(|option| option.value.to_lowercase())
+        .unwrap_or_default();
+
+    let choices = client
+        .list_webcams()
+        .await?
+        .into_iter()
+        .filter(|webcam| webcam.name.to_lowercase().contains(&partial))
+        .take(25)
+        .map(|webcam| AutocompleteChoice::new(webcam.name.clone(), webcam.name))
+        .collect();
+    interaction
+        .create_response(
+            ctx,
+            CreateInteractionResponse::Autocomplete(
+                CreateAutocompleteResponse::new().set_choices(choices),
+            ),
+        )
+        .await?;
+    Ok(())
+}
+
+async fn take_snapshot(
+    client: &Client,
+    name: Option<&str>,
+) -> Result<Option<(String, webcam::Image)>> {
+    let Some(webcam) = webcam::resolve_webcam(client, name).await? else {
+        return Ok(None);
+    };
+    let image = webcam::get_webcam_snapshot(client, &webcam).await?;
+    Ok(Some((webcam, image)))
+}
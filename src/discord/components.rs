@@ -0,0 +1,74 @@
+mod announcement;
+mod exclude_object;
+mod macros;
+mod power;
+mod power_off;
+mod print;
+mod queue;
+mod update;
+
+use anyhow::{anyhow, Result};
+use serenity::all::{ComponentInteraction, Context, CreateActionRow, CreateButton};
+
+pub use announcement::dismiss_button as announcement_dismiss_button;
+pub use exclude_object::select_menu as exclude_object_menu;
+pub use macros::buttons as macro_buttons;
+pub use power::buttons as power_buttons;
+pub use power_off::keep_on_button as power_off_keep_on_button;
+pub use print::{confirm_buttons as print_confirm_buttons, BUSY_MESSAGE as PRINT_BUSY_MESSAGE};
+pub use update::buttons as update_buttons;
+
+/// Discord's limit for the custom id of a component.
+const MAX_CUSTOM_ID_LENGTH: usize = 100;
+const BUTTONS_PER_ROW: usize = 5;
+const MAX_ROWS: usize = 5;
+
+pub async fn handle(ctx: &Context, interaction: &ComponentInteraction) -> Result<()> {
+    let custom_id = interaction.data.custom_id.as_str();
+    if announcement::handles(custom_id) {
+        return announcement::handle(ctx, interaction).await;
+    }
+    if exclude_object::handles(custom_id) {
+        return exclude_object::handle(ctx, interaction).await;
+    }
+    if macros::handles(custom_id) {
+        return macros::handle(ctx, interaction).await;
+    }
+    if power::handles(custom_id) {
+        return power::handle(ctx, interaction).await;
+    }
+    if power_off::handles(custom_id) {
+        return power_off::handle(ctx, interaction).await;
+    }
+    if print::handles(custom_id) {
+        return print::handle(ctx, interaction).await;
+    }
+    if queue::handles(custom_id) {
+        return queue::handle(ctx, interaction).await;
+    }
+    if update::handles(custom_id) {
+        return update::handle(ctx, interaction).await;
+    }
+    Err(anyhow!("unknown component: {:?}", custom_id))
+}
+
+/// Buttons offering to print or queue an uploaded file.
+pub fn upload_buttons(file_name: &str) -> Vec<CreateActionRow> {
+    let buttons = [print::start_button(file_name), queue::add_button(file_name)]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    if buttons.is_empty() {
+        return vec![];
+    }
+    vec![CreateActionRow::Buttons(buttons)]
+}
+
+/// Lays the buttons out in rows, dropping those that do not fit in a message.
+fn button_rows(buttons: Vec<CreateButton>) -> Vec<CreateActionRow> {
+    buttons
+        .chunks(BUTTONS_PER_ROW)
+        .take(MAX_ROWS)
+        .map(|row| CreateActionRow::Buttons(row.to_vec()))
+        .collect()
+}
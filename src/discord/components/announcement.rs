@@ -0,0 +1,57 @@
+use anyhow::Result;
+use serenity::all::{
+    ButtonStyle, ComponentInteraction, Context, CreateActionRow, CreateButton,
+    EditInteractionResponse,
+};
+
+use super::MAX_CUSTOM_ID_LENGTH;
+use crate::discord::{auth, typemap::Moonraker};
+
+const DISMISS_PREFIX: &str = "announcement_dismiss:";
+
+/// A button dismissing the announcement, if its id fits in a custom id.
+pub fn dismiss_button(entry_id: &str) -> Option<CreateActionRow> {
+    if DISMISS_PREFIX.len() + entry_id.len() > MAX_CUSTOM_ID_LENGTH {
+        return None;
+    }
+
+    Some(CreateActionRow::Buttons(vec![CreateButton::new(format!(
+        "{}{}",
+        DISMISS_PREFIX, entry_id
+    ))
+    .label("Dismiss")
+    .style(ButtonStyle::Secondary)]))
+}
+
+pub fn handles(custom_id: &str) -> bool {
+    custom_id.starts_with(DISMISS_PREFIX)
+}
+
+pub async fn handle(ctx: &Context, interaction: &ComponentInteraction) -> Result<()> {
+    if !auth::is_authorized(ctx, interaction.user.id, interaction.member.as_ref()).await {
+        interaction
+            .create_response(ctx, auth::denied_response())
+            .await?;
+        return Ok(());
+    }
+    let Some(entry_id) = interaction.data.custom_id.strip_prefix(DISMISS_PREFIX) else {
+        return Ok(());
+    };
+    let client = {
+        let data_read = ctx.data.read().await;
+        data_read.get::<Moonraker>().unwrap().clone()
+    };
+    interaction.defer(ctx).await?;
+
+    let response = match client.dismiss_announcement(entry_id).await {
+        Ok(()) => EditInteractionResponse::new()
+            .content(format!("Dismissed by {}", interaction.user.name))
+            .components(vec![]),
+        Err(err) => {
+            tracing::error!("error dismissing announcement {:?}: {:?}", entry_id, err);
+            EditInteractionResponse::new().content(format!("Failed to dismiss: {}", err))
+        }
+    };
+    interaction.edit_response(ctx, response).await?;
+    Ok(())
+}
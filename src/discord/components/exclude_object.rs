@@ -0,0 +1,122 @@
+use anyhow::Result;
+use serenity::all::{
+    ButtonStyle, ComponentInteraction, ComponentInteractionDataKind, Context, CreateActionRow,
+    CreateButton, CreateInteractionResponse, CreateInteractionResponseMessage, CreateSelectMenu,
+    CreateSelectMenuKind, CreateSelectMenuOption, EditInteractionResponse,
+};
+
+use super::MAX_CUSTOM_ID_LENGTH;
+use crate::{
+    discord::{auth, typemap::Moonraker},
+    moonraker::ObjectInformation,
+};
+
+const SELECT_ID: &str = "exclude_object";
+const CONFIRM_PREFIX: &str = "exclude_object_confirm:";
+const CANCEL_ID: &str = "exclude_object_cancel";
+const MAX_OPTIONS: usize = 25;
+
+/// A select menu with the objects that can still be excluded.
+pub fn select_menu(objects: &[ObjectInformation]) -> Option<CreateActionRow> {
+    let options = objects
+        .iter()
+        .filter(|object| {
+            !object.excluded && CONFIRM_PREFIX.len() + object.name.len() <= MAX_CUSTOM_ID_LENGTH
+        })
+        .take(MAX_OPTIONS)
+        .map(|object| CreateSelectMenuOption::new(&object.name, &object.name))
+        .collect::<Vec<_>>();
+    if options.is_empty() {
+        return None;
+    }
+
+    Some(CreateActionRow::SelectMenu(
+        CreateSelectMenu::new(SELECT_ID, CreateSelectMenuKind::String { options })
+            .placeholder("Exclude object"),
+    ))
+}
+
+pub fn handles(custom_id: &str) -> bool {
+    custom_id == SELECT_ID || custom_id == CANCEL_ID || custom_id.starts_with(CONFIRM_PREFIX)
+}
+
+pub async fn handle(ctx: &Context, interaction: &ComponentInteraction) -> Result<()> {
+    if !auth::is_owner(ctx, interaction.user.id).await {
+        interaction
+            .create_response(ctx, auth::denied_response())
+            .await?;
+        return Ok(());
+    }
+
+    let custom_id = interaction.data.custom_id.as_str();
+    if let Some(name) = custom_id.strip_prefix(CONFIRM_PREFIX) {
+        return exclude(ctx, interaction, name).await;
+    }
+    if custom_id == CANCEL_ID {
+        interaction
+            .create_response(
+                ctx,
+                CreateInteractionResponse::UpdateMessage(
+                    CreateInteractionResponseMessage::new()
+                        .content("Cancelled")
+                        .components(vec![]),
+                ),
+            )
+            .await?;
+        return Ok(());
+    }
+
+    let ComponentInteractionDataKind::StringSelect { values } = &interaction.data.kind else {
+        return Ok(());
+    };
+    let Some(name) = values.first() else {
+        return Ok(());
+    };
+    interaction
+        .create_response(
+            ctx,
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .content(format!("Exclude **{}** from the print?", name))
+                    .components(vec![CreateActionRow::Buttons(vec![
+                        CreateButton::new(format!("{}{}", CONFIRM_PREFIX, name))
+                            .label("Exclude")
+                            .style(ButtonStyle::Danger),
+                        CreateButton::new(CANCEL_ID)
+                            .label("Cancel")
+                            .style(ButtonStyle::Secondary),
+                    ])])
+                    .ephemeral(true),
+            ),
+        )
+        .await?;
+    Ok(())
+}
+
+async fn exclude(ctx: &Context, interaction: &ComponentInteraction, name: &str) -> Result<()> {
+    let client = {
+        let data_read = ctx.data.read().await;
+        data_read.get::<Moonraker>().unwrap().clone()
+    };
+    interaction.defer(ctx).await?;
+
+    let content = match client
+        .run_gcode(format!("EXCLUDE_OBJECT NAME={}", name))
+        .await
+    {
+        Ok(()) => format!("Excluded **{}**", name),
+        Err(err) => {
+            tracing::error!("error excluding object {:?}: {:?}", name, err);
+            format!("Failed to exclude **{}**: {}", name, err)
+        }
+    };
+    interaction
+        .edit_response(
+            ctx,
+            EditInteractionResponse::new()
+                .content(content)
+                .components(vec![]),
+        )
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use anyhow::{anyhow, Result};
+use serenity::all::{
+    ButtonStyle, ComponentInteraction, Context, CreateActionRow, CreateButton,
+    EditInteractionResponse,
+};
+
+use super::button_rows;
+use crate::discord::{auth, console, typemap::DiscordConfig, Macro};
+
+const PREFIX: &str = "macro:";
+
+/// Rows of buttons running the configured macros, identified by their index.
+pub fn buttons(macros: &[Macro]) -> Vec<CreateActionRow> {
+    button_rows(
+        macros
+            .iter()
+            .enumerate()
+            .map(|(index, r#macro)| {
+                CreateButton::new(format!("{}{}", PREFIX, index))
+                    .label(&r#macro.label)
+                    .style(ButtonStyle::Primary)
+            })
+            .collect(),
+    )
+}
+
+pub fn handles(custom_id: &str) -> bool {
+    custom_id.starts_with(PREFIX)
+}
+
+pub async fn handle(ctx: &Context, interaction: &ComponentInteraction) -> Result<()> {
+    if !auth::is_authorized(ctx, interaction.user.id, interaction.member.as_ref()).await {
+        interaction
+            .create_response(ctx, auth::denied_response())
+            .await?;
+        return Ok(());
+    }
+
+    let config = {
+        let data_read = ctx.data.read().await;
+        data_read.get::<DiscordConfig>().unwrap().clone()
+    };
+    let r#macro = interaction
+        .data
+        .custom_id
+        .strip_prefix(PREFIX)
+        .and_then(|index| index.parse::<usize>().ok())
+        .and_then(|index| config.macros.get(index))
+        .ok_or_else(|| anyhow!("unknown macro: {:?}", interaction.data.custom_id))?;
+    interaction.defer_ephemeral(ctx).await?;
+
+    let content = console::run_gcode(ctx, &r#macro.gcode).await;
+    interaction
+        .edit_response(
+            ctx,
+            EditInteractionResponse::new().content(format!("**{}**\n{}", r#macro.label, content)),
+        )
+        .await?;
+    Ok(())
+}
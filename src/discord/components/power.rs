@@ -0,0 +1,71 @@
+use anyhow::Result;
+use serenity::all::{
+    ButtonStyle, ComponentInteraction, Context, CreateActionRow, CreateButton,
+    EditInteractionResponse,
+};
+
+use super::{button_rows, MAX_CUSTOM_ID_LENGTH};
+use crate::{
+    discord::{auth, typemap::Moonraker},
+    moonraker::PowerDevice,
+};
+
+const TOGGLE_PREFIX: &str = "power_toggle:";
+
+/// Rows of buttons toggling the power devices, showing their status.
+pub fn buttons(devices: &[PowerDevice]) -> Vec<CreateActionRow> {
+    button_rows(
+        devices
+            .iter()
+            .filter(|device| TOGGLE_PREFIX.len() + device.name.len() <= MAX_CUSTOM_ID_LENGTH)
+            .map(|device| {
+                CreateButton::new(format!("{}{}", TOGGLE_PREFIX, device.name))
+                    .label(format!("{}: {}", device.name, device.status))
+                    .style(if device.status == "on" {
+                        ButtonStyle::Success
+                    } else {
+                        ButtonStyle::Secondary
+                    })
+            })
+            .collect(),
+    )
+}
+
+pub fn handles(custom_id: &str) -> bool {
+    custom_id.starts_with(TOGGLE_PREFIX)
+}
+
+pub async fn handle(ctx: &Context, interaction: &ComponentInteraction) -> Result<()> {
+    if !auth::is_authorized(ctx, interaction.user.id, interaction.member.as_ref()).await {
+        interaction
+            .create_response(ctx, auth::denied_response())
+            .await?;
+        return Ok(());
+    }
+    let Some(device) = interaction.data.custom_id.strip_prefix(TOGGLE_PREFIX) else {
+        return Ok(());
+    };
+    let client = {
+        let data_read = ctx.data.read().await;
+        data_read.get::<Moonraker>().unwrap().clone()
+    };
+    interaction.defer(ctx).await?;
+
+    let mut response = EditInteractionResponse::new();
+    match client.set_power_device(device, "toggle").await {
+        Ok(status) => tracing::info!("switched {:?} {}", device, status),
+        Err(err) => {
+            tracing::error!("error toggling {:?}: {:?}", device, err);
+            response = response.content(format!("Failed to toggle {}: {}", device, err));
+        }
+    }
+    let devices = client.list_power_devices().await?;
+    let devices = devices
+        .into_iter()
+        .map(PowerDevice::from)
+        .collect::<Vec<_>>();
+    interaction
+        .edit_response(ctx, response.components(buttons(&devices)))
+        .await?;
+    Ok(())
+}
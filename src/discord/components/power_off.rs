@@ -0,0 +1,74 @@
+use std::sync::atomic::Ordering;
+
+use anyhow::Result;
+use serenity::all::{
+    ButtonStyle, ComponentInteraction, Context, CreateActionRow, CreateButton,
+    CreateInteractionResponse, CreateInteractionResponseMessage,
+};
+
+use crate::discord::{auth, typemap::PendingPowerOff};
+
+const KEEP_ON_PREFIX: &str = "power_off_keep:";
+
+/// A button cancelling the scheduled power-off with the given id.
+pub fn keep_on_button(id: u64) -> CreateActionRow {
+    CreateActionRow::Buttons(vec![CreateButton::new(format!("{}{}", KEEP_ON_PREFIX, id))
+        .label("Keep on")
+        .style(ButtonStyle::Secondary)])
+}
+
+pub fn handles(custom_id: &str) -> bool {
+    custom_id.starts_with(KEEP_ON_PREFIX)
+}
+
+pub async fn handle(ctx: &Context, interaction: &ComponentInteraction) -> Result<()> {
+    let reply = |content: &str| {
+        CreateInteractionResponse::Message(
+            CreateInteractionResponseMessage::new()
+                .content(content)
+                .ephemeral(true),
+        )
+    };
+    if !auth::is_authorized(ctx, interaction.user.id, interaction.member.as_ref()).await {
+        interaction
+            .create_response(ctx, auth::denied_response())
+            .await?;
+        return Ok(());
+    }
+    let Some(id) = interaction
+        .data
+        .custom_id
+        .strip_prefix(KEEP_ON_PREFIX)
+        .and_then(|id| id.parse::<u64>().ok())
+    else {
+        return Ok(());
+    };
+    let pending = {
+        let data_read = ctx.data.read().await;
+        data_read.get::<PendingPowerOff>().unwrap().clone()
+    };
+
+    if pending
+        .compare_exchange(id, 0, Ordering::Relaxed, Ordering::Relaxed)
+        .is_err()
+    {
+        interaction
+            .create_response(ctx, reply("The power-off is no longer pending"))
+            .await?;
+        return Ok(());
+    }
+    interaction
+        .create_response(
+            ctx,
+            CreateInteractionResponse::UpdateMessage(
+                CreateInteractionResponseMessage::new()
+                    .content(format!(
+                        "Keeping the printer on, as asked by {}",
+                        interaction.user.name
+                    ))
+                    .components(vec![]),
+            ),
+        )
+        .await?;
+    Ok(())
+}
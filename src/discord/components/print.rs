@@ -0,0 +1,111 @@
+use anyhow::Result;
+use serenity::all::{
+    ButtonStyle, ComponentInteraction, Context, CreateActionRow, CreateButton,
+    CreateInteractionResponse, CreateInteractionResponseMessage, EditInteractionResponse,
+};
+
+use super::MAX_CUSTOM_ID_LENGTH;
+use crate::{
+    discord::{
+        auth,
+        typemap::{CurrentStatus, Moonraker},
+    },
+    moonraker::State,
+};
+
+pub const BUSY_MESSAGE: &str = "The printer is already printing";
+
+const START_PREFIX: &str = "print_start:";
+const CANCEL_ID: &str = "print_cancel";
+
+/// Buttons confirming to print the file, if its name fits in a custom id.
+pub fn confirm_buttons(file_name: &str) -> Option<CreateActionRow> {
+    Some(CreateActionRow::Buttons(vec![
+        start_button(file_name)?,
+        CreateButton::new(CANCEL_ID)
+            .label("Cancel")
+            .style(ButtonStyle::Secondary),
+    ]))
+}
+
+/// A button starting to print the file, if its name fits in a custom id.
+pub fn start_button(file_name: &str) -> Option<CreateButton> {
+    if START_PREFIX.len() + file_name.len() > MAX_CUSTOM_ID_LENGTH {
+        return None;
+    }
+
+    Some(
+        CreateButton::new(format!("{}{}", START_PREFIX, file_name))
+            .label("Print")
+            .style(ButtonStyle::Success),
+    )
+}
+
+pub fn handles(custom_id: &str) -> bool {
+    custom_id == CANCEL_ID || custom_id.starts_with(START_PREFIX)
+}
+
+pub async fn handle(ctx: &Context, interaction: &ComponentInteraction) -> Result<()> {
+    let update = |content: &str| {
+        CreateInteractionResponse::UpdateMessage(
+            CreateInteractionResponseMessage::new()
+                .content(content)
+                .embeds(vec![])
+                .components(vec![]),
+        )
+    };
+    let Some(file_name) = interaction.data.custom_id.strip_prefix(START_PREFIX) else {
+        interaction
+            .create_response(ctx, update("Cancelled"))
+            .await?;
+        return Ok(());
+    };
+    let reply = |content: &str| {
+        CreateInteractionResponse::Message(
+            CreateInteractionResponseMessage::new()
+                .content(content)
+                .ephemeral(true),
+        )
+    };
+    if !auth::is_authorized(ctx, interaction.user.id, interaction.member.as_ref()).await {
+        interaction
+            .create_response(ctx, auth::denied_response())
+            .await?;
+        return Ok(());
+    }
+    let (client, state) = {
+        let data_read = ctx.data.read().await;
+        let state = data_read
+            .get::<CurrentStatus>()
+            .unwrap()
+            .borrow()
+            .state
+            .clone();
+        (data_read.get::<Moonraker>().unwrap().clone(), state)
+    };
+    if matches!(state, State::Printing | State::Paused) {
+        interaction
+            .create_response(ctx, reply(BUSY_MESSAGE))
+            .await?;
+        return Ok(());
+    }
+    interaction.defer(ctx).await?;
+
+    let content = match client.start_print(file_name).await {
+        Ok(()) => format!("Started printing {}", file_name),
+        Err(err) => {
+            tracing::error!("error starting print of {:?}: {:?}", file_name, err);
+            format!("Failed to start printing {}: {}", file_name, err)
+        }
+    };
+    interaction
+        .edit_response(
+            ctx,
+            EditInteractionResponse::new()
+                .content(content)
+                .embeds(vec![])
+                .components(vec![]),
+        )
+        .await?;
+    Ok(())
+}
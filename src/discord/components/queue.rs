@@ -0,0 +1,61 @@
+use anyhow::Result;
+use serenity::all::{
+    ButtonStyle, ComponentInteraction, Context, CreateButton, EditInteractionResponse,
+};
+
+use super::MAX_CUSTOM_ID_LENGTH;
+use crate::discord::{auth, typemap::Moonraker};
+
+const ADD_PREFIX: &str = "queue_add:";
+
+/// A button adding the file to the job queue, if its name fits in a custom id.
+pub fn add_button(file_name: &str) -> Option<CreateButton> {
+    if ADD_PREFIX.len() + file_name.len() > MAX_CUSTOM_ID_LENGTH {
+        return None;
+    }
+
+    Some(
+        CreateButton::new(format!("{}{}", ADD_PREFIX, file_name))
+            .label("Add to queue")
+            .style(ButtonStyle::Primary),
+    )
+}
+
+pub fn handles(custom_id: &str) -> bool {
+    custom_id.starts_with(ADD_PREFIX)
+}
+
+pub async fn handle(ctx: &Context, interaction: &ComponentInteraction) -> Result<()> {
+    if !auth::is_authorized(ctx, interaction.user.id, interaction.member.as_ref()).await {
+        interaction
+            .create_response(ctx, auth::denied_response())
+            .await?;
+        return Ok(());
+    }
+    let Some(file_name) = interaction.data.custom_id.strip_prefix(ADD_PREFIX) else {
+        return Ok(());
+    };
+    let client = {
+        let data_read = ctx.data.read().await;
+        data_read.get::<Moonraker>().unwrap().clone()
+    };
+    interaction.defer(ctx).await?;
+
+    let content = match client.enqueue_job(file_name).await {
+        Ok(_) => format!("Added {} to the job queue", file_name),
+        Err(err) => {
+            tracing::error!("error adding {:?} to the job queue: {:?}", file_name, err);
+            format!("Failed to add {} to the job queue: {}", file_name, err)
+        }
+    };
+    interaction
+        .edit_response(
+            ctx,
+            EditInteractionResponse::new()
+                .content(content)
+                .embeds(vec![])
+                .components(vec![]),
+        )
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,196 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::Result;
+use serenity::all::{
+    ButtonStyle, ChannelId, ChannelType, ComponentInteraction, Context, CreateActionRow,
+    CreateButton, CreateInteractionResponse, CreateInteractionResponseMessage, CreateMessage,
+    CreateThread, EditInteractionResponse, Mention,
+};
+use tokio::{select, time};
+
+use super::{button_rows, MAX_CUSTOM_ID_LENGTH};
+use crate::{
+    discord::{
+        auth,
+        retry::retry,
+        typemap::{CurrentStatus, Moonraker},
+    },
+    moonraker::{AvailableUpdate, Client, State},
+};
+
+const UPGRADE_PREFIX: &str = "update:";
+/// How often the collected update log is posted.
+const LOG_INTERVAL: Duration = Duration::from_secs(2);
+const MAX_LOG_LENGTH: usize = 1900;
+
+/// Rows of buttons starting the updates.
+pub fn buttons(updates: &[AvailableUpdate]) -> Vec<CreateActionRow> {
+    button_rows(
+        updates
+            .iter()
+            .filter(|update| UPGRADE_PREFIX.len() + update.name.len() <= MAX_CUSTOM_ID_LENGTH)
+            .map(|update| {
+                CreateButton::new(format!("{}{}", UPGRADE_PREFIX, update.name))
+                    .label(format!("Update {}", update.name))
+                    .style(ButtonStyle::Primary)
+            })
+            .collect(),
+    )
+}
+
+pub fn handles(custom_id: &str) -> bool {
+    custom_id.starts_with(UPGRADE_PREFIX)
+}
+
+pub async fn handle(ctx: &Context, interaction: &ComponentInteraction) -> Result<()> {
+    let reply = |content: String| {
+        CreateInteractionResponse::Message(
+            CreateInteractionResponseMessage::new()
+                .content(content)
+                .ephemeral(true),
+        )
+    };
+    if !auth::is_owner(ctx, interaction.user.id).await {
+        interaction
+            .create_response(ctx, reply("Only the printer owner can update".to_string()))
+            .await?;
+        return Ok(());
+    }
+    let Some(name) = interaction.data.custom_id.strip_prefix(UPGRADE_PREFIX) else {
+        return Ok(());
+    };
+    let (client, state) = {
+        let data_read = ctx.data.read().await;
+        let state = data_read
+            .get::<CurrentStatus>()
+            .unwrap()
+            .borrow()
+            .state
+            .clone();
+        (data_read.get::<Moonraker>().unwrap().clone(), state)
+    };
+    if matches!(state, State::Printing | State::Paused) {
+        interaction
+            .create_response(
+                ctx,
+                reply("Updates are not allowed while printing".to_string()),
+            )
+            .await?;
+        return Ok(());
+    }
+
+    interaction.defer_ephemeral(ctx).await?;
+
+    let content = match create_update_thread(ctx, interaction.channel_id, &client, name).await {
+        Ok(Some(thread_id)) => {
+            tracing::info!("updating {:?}", name);
+            let ctx = ctx.clone();
+            let update = name.to_string();
+            tokio::spawn(async move {
+                if let Err(err) = follow_update(&ctx, thread_id, client, update).await {
+                    tracing::error!("error following update: {:?}", err);
+                }
+            });
+            format!("Updating {} in {}", name, Mention::from(thread_id))
+        }
+        Ok(None) => "Another update is running".to_string(),
+        Err(err) => {
+            tracing::error!("error starting update of {:?}: {:?}", name, err);
+            format!("Failed to start updating {}: {}", name, err)
+        }
+    };
+    interaction
+        .edit_response(ctx, EditInteractionResponse::new().content(content))
+        .await?;
+    Ok(())
+}
+
+/// Creates the thread following the update, unless another update is running.
+async fn create_update_thread(
+    ctx: &Context,
+    channel_id: ChannelId,
+    client: &Client,
+    name: &str,
+) -> Result<Option<ChannelId>> {
+    if client.get_update_status().await?.busy {
+        return Ok(None);
+    }
+    let thread = retry(|| {
+        channel_id.create_thread(
+            ctx,
+            CreateThread::new(format!("Updating {}", name)).kind(ChannelType::PublicThread),
+        )
+    })
+    .await?;
+    Ok(Some(thread.id))
+}
+
+/// Runs the update and posts its log to the thread until it completes.
+async fn follow_update(
+    ctx: &Context,
+    thread_id: ChannelId,
+    client: Arc<Client>,
+    name: String,
+) -> Result<()> {
+    // Subscribing before starting the update catches the whole log
+    let mut log = client.subscribe_update_response().await?;
+    let upgrade = {
+        let client = Arc::clone(&client);
+        let name = name.clone();
+        tokio::spawn(async move { client.upgrade(&name).await })
+    };
+    tokio::pin!(upgrade);
+
+    let mut lines = Vec::new();
+    let mut interval = time::interval(LOG_INTERVAL);
+    let mut upgrade_done = false;
+    let mut complete = false;
+    while !complete {
+        select! {
+            res = log.next() => match res {
+                Some(Ok((response,))) => {
+                    complete = response.complete;
+                    lines.push(response.message);
+                }
+                Some(Err(err)) => tracing::error!("error reading update log: {:?}", err),
+                None => complete = true,
+            },
+            res = &mut upgrade, if !upgrade_done => {
+                upgrade_done = true;
+                if let Err(err) = res? {
+                    lines.push(format!("!! {}", err));
+                    complete = true;
+                }
+            },
+            _ = interval.tick() => post_log(ctx, thread_id, &mut lines).await?,
+        }
+    }
+    post_log(ctx, thread_id, &mut lines).await?;
+    let message = CreateMessage::new().content(format!("Finished updating {}", name));
+    retry(|| thread_id.send_message(ctx, message.clone())).await?;
+    Ok(())
+}
+
+/// Posts the collected lines in as few messages as possible.
+async fn post_log(ctx: &Context, thread_id: ChannelId, lines: &mut Vec<String>) -> Result<()> {
+    let mut chunk = String::new();
+    for line in lines.drain(..) {
+        if !chunk.is_empty() && chunk.len() + line.len() + 1 > MAX_LOG_LENGTH {
+            send_chunk(ctx, thread_id, &chunk).await?;
+            chunk.clear();
+        }
+        let line = line.chars().take(MAX_LOG_LENGTH).collect::<String>();
+        chunk.push_str(&line);
+        chunk.push('\n');
+    }
+    if !chunk.is_empty() {
+        send_chunk(ctx, thread_id, &chunk).await?;
+    }
+    Ok(())
+}
+
+async fn send_chunk(ctx: &Context, thread_id: ChannelId, chunk: &str) -> Result<()> {
+    let message = CreateMessage::new().content(format!("```\n{}```", chunk.replace("```", "'''")));
+    retry(|| thread_id.send_message(ctx, message.clone())).await?;
+    Ok(())
+}
@@ -0,0 +1,51 @@
+use std::time::Duration;
+
+use serenity::all::Context;
+use tokio::sync::broadcast::error::TryRecvError;
+
+use super::typemap::{Console, Moonraker};
+
+/// How long to wait for console lines that are still being forwarded after
+/// the script completed.
+const RESPONSE_GRACE: Duration = Duration::from_millis(250);
+const MAX_REPLY_LENGTH: usize = 1900;
+
+/// Runs a G-code script and formats the console lines Klipper responded with
+/// as a message.
+pub async fn run_gcode(ctx: &Context, script: &str) -> String {
+    let (client, mut console) = {
+        let data_read = ctx.data.read().await;
+        (
+            data_read.get::<Moonraker>().unwrap().clone(),
+            data_read.get::<Console>().unwrap().subscribe(),
+        )
+    };
+
+    let result = client.run_gcode(script).await;
+    tokio::time::sleep(RESPONSE_GRACE).await;
+    let mut lines = Vec::new();
+    loop {
+        match console.try_recv() {
+            Ok(line) => lines.push(line),
+            Err(TryRecvError::Lagged(_)) => continue,
+            Err(_) => break,
+        }
+    }
+    if let Err(err) = result {
+        tracing::error!("error running {:?}: {:?}", script, err);
+        lines.push(format!("!! {}", err));
+    }
+    if lines.is_empty() {
+        return format!("`{}`: ok", script);
+    }
+
+    // Keep the last lines, they are the most relevant
+    let mut output = String::new();
+    for line in lines.iter().rev() {
+        if output.len() + line.len() + 1 > MAX_REPLY_LENGTH {
+            break;
+        }
+        output.insert_str(0, &format!("{}\n", line));
+    }
+    format!("```\n{}```", output)
+}
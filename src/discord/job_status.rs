@@ -1,10 +1,20 @@
-use serenity::all::{CreateEmbed, CreateMessage, EditMessage};
+use serenity::all::{CreateActionRow, CreateEmbed, CreateMessage, EditMessage};
 use std::collections::HashMap;
 
-use crate::moonraker::{JobInfo, State};
+use super::{
+    components,
+    plate_map::{self, PlateMap},
+};
+use crate::moonraker::{JobInfo, JobQueue, State};
 
+/// Queued jobs listed in the embed.
+const QUEUE_PREVIEW: usize = 3;
+
+#[derive(Clone, PartialEq)]
 pub struct JobStatusMessage {
     embed: CreateEmbed,
+    components: Vec<CreateActionRow>,
+    plate_map: Option<PlateMap>,
 }
 
 struct ObjectData {
@@ -12,9 +22,21 @@ struct ObjectData {
     pub excluded: u8,
 }
 
-impl From<(State, JobInfo)> for JobStatusMessage {
-    fn from(tuple: (State, JobInfo)) -> Self {
-        let (state, job) = tuple;
+impl JobStatusMessage {
+    /// Shows the attachment with the given file name as the embed image.
+    pub fn snapshot(mut self, file_name: &str) -> Self {
+        self.embed = self.embed.attachment(file_name);
+        self
+    }
+
+    pub fn plate_map(&self) -> Option<&PlateMap> {
+        self.plate_map.as_ref()
+    }
+}
+
+impl From<(State, JobInfo, JobQueue)> for JobStatusMessage {
+    fn from(tuple: (State, JobInfo, JobQueue)) -> Self {
+        let (state, job, queue) = tuple;
         let mut object_map = HashMap::new();
         for object in job.objects.iter() {
             let name = object
@@ -36,11 +58,19 @@ impl From<(State, JobInfo)> for JobStatusMessage {
             .title("Job Status")
             .field("State", state.to_string(), true)
             .field(
-                "Layers",
+                if job.layers_estimated {
+                    "Layers (estimated)"
+                } else {
+                    "Layers"
+                },
                 format!("{} / {}", job.current_layer, job.total_layer),
                 true,
             );
 
+        if let Some(current_object) = &job.current_object {
+            embed = embed.field("Current Object", current_object, false);
+        }
+
         if !job.objects.is_empty() {
             embed = embed.field(
                 format!(
@@ -59,18 +89,59 @@ impl From<(State, JobInfo)> for JobStatusMessage {
             );
         }
 
-        JobStatusMessage { embed }
+        if !queue.jobs.is_empty() {
+            let mut next = queue
+                .jobs
+                .iter()
+                .take(QUEUE_PREVIEW)
+                .map(|job| job.file_name.clone())
+                .collect::<Vec<_>>();
+            if queue.jobs.len() > QUEUE_PREVIEW {
+                next.push(format!("and {} more", queue.jobs.len() - QUEUE_PREVIEW));
+            }
+            embed = embed.field(
+                if queue.state == "paused" {
+                    "Up Next (queue paused)"
+                } else {
+                    "Up Next"
+                },
+                next.join("\n"),
+                false,
+            );
+        }
+
+        let plate_map = PlateMap::from_job(&job);
+        if plate_map.is_some() {
+            embed = embed.thumbnail(format!("attachment://{}", plate_map::FILE_NAME));
+        }
+
+        let components = match state {
+            State::Printing | State::Paused => components::exclude_object_menu(&job.objects)
+                .into_iter()
+                .collect(),
+            _ => vec![],
+        };
+
+        JobStatusMessage {
+            embed,
+            components,
+            plate_map,
+        }
     }
 }
 
 impl From<JobStatusMessage> for CreateMessage {
     fn from(value: JobStatusMessage) -> Self {
-        CreateMessage::new().embed(value.embed)
+        CreateMessage::new()
+            .embed(value.embed)
+            .components(value.components)
     }
 }
 
 impl From<JobStatusMessage> for EditMessage {
     fn from(value: JobStatusMessage) -> Self {
-        EditMessage::new().embed(value.embed)
+        EditMessage::new()
+            .embed(value.embed)
+            .components(value.components)
     }
 }
@@ -0,0 +1,230 @@
+use std::time::Duration;
+
+use serenity::all::{
+    Attachment, ChannelId, ChannelType, Context, CreateAttachment, CreateMessage, CreateThread,
+    EditMessage, MessageId,
+};
+
+use super::{
+    job_status::JobStatusMessage,
+    plate_map::{self, PlateMap},
+    retry::{is_unknown_message, retry},
+    timelapse::Timelapse,
+};
+use tokio::time::Instant;
+
+use crate::moonraker::webcam::Image;
+
+const SNAPSHOT_NAME: &str = "snapshot";
+/// Least time between re-renders of the plate map that only move the
+/// highlight, as Klipper switches the current object several times per layer.
+const HIGHLIGHT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The Discord thread of a job, holding its status message.
+pub struct JobThread {
+    pub file_name: String,
+    pub thread_id: ChannelId,
+    pub timelapse: Timelapse,
+    message_id: Option<MessageId>,
+    attachments: Vec<Attachment>,
+    snapshot_file_name: Option<String>,
+    /// The plate map attached to the message, and when it was rendered.
+    plate_map: Option<(PlateMap, Instant)>,
+    edit_interval: Duration,
+    published: Option<JobStatusMessage>,
+    pending: Option<JobStatusMessage>,
+    last_edit: Option<Instant>,
+}
+
+impl JobThread {
+    pub async fn create(
+        ctx: &Context,
+        channel_id: ChannelId,
+        file_name: String,
+        edit_interval: Duration,
+    ) -> serenity::Result<Self> {
+        let thread = retry(|| {
+            channel_id.create_thread(
+                ctx,
+                CreateThread::new(file_name.clone()).kind(ChannelType::PublicThread),
+            )
+        })
+        .await?;
+
+        Ok(Self {
+            file_name,
+            thread_id: thread.id,
+            timelapse: Timelapse::default(),
+            message_id: None,
+            attachments: Vec::new(),
+            snapshot_file_name: None,
+            plate_map: None,
+            edit_interval,
+            published: None,
+            pending: None,
+            last_edit: None,
+        })
+    }
+
+    pub async fn send(&self, ctx: &Context, message: CreateMessage) -> serenity::Result<()> {
+        let thread_id = self.thread_id;
+        retry(|| thread_id.send_message(ctx, message.clone())).await?;
+        Ok(())
+    }
+
+    /// Updates the status message if it changed, keeping the current snapshot.
+    /// Edits are held back until the edit interval has passed, unless `flush` is set.
+    pub async fn update(
+        &mut self,
+        ctx: &Context,
+        status: JobStatusMessage,
+        flush: bool,
+    ) -> serenity::Result<()> {
+        if self.published.as_ref() == Some(&status) {
+            self.pending = None;
+            return Ok(());
+        }
+        let due = self
+            .last_edit
+            .is_none_or(|last_edit| last_edit + self.edit_interval <= Instant::now());
+        if flush || due {
+            self.pending = None;
+            return self.publish(ctx, status, None).await;
+        }
+
+        self.pending = Some(status);
+        Ok(())
+    }
+
+    /// When the held back status update should be published.
+    pub fn pending_deadline(&self) -> Option<Instant> {
+        self.pending.as_ref()?;
+        self.last_edit
+            .map(|last_edit| last_edit + self.edit_interval)
+            .or_else(|| Some(Instant::now()))
+    }
+
+    /// Publishes the held back status update, if any.
+    pub async fn flush(&mut self, ctx: &Context) -> serenity::Result<()> {
+        match self.pending.take() {
+            Some(status) => self.publish(ctx, status, None).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Updates the status message and replaces its snapshot.
+    pub async fn update_snapshot(
+        &mut self,
+        ctx: &Context,
+        status: JobStatusMessage,
+        snapshot: &Image,
+    ) -> serenity::Result<()> {
+        let file_name = snapshot.file_name(SNAPSHOT_NAME);
+        let attachment = CreateAttachment::bytes(snapshot.data.clone(), file_name.clone());
+        self.snapshot_file_name = Some(file_name);
+        self.pending = None;
+        self.publish(ctx, status, Some(attachment)).await
+    }
+
+    /// Edits the status message, or sends a new one if there is none yet or
+    /// it has been deleted.
+    async fn publish(
+        &mut self,
+        ctx: &Context,
+        status: JobStatusMessage,
+        snapshot: Option<CreateAttachment>,
+    ) -> serenity::Result<()> {
+        let thread_id = self.thread_id;
+        if let Some(message_id) = self.message_id {
+            let plate_map_changed = self.plate_map_due(&status);
+            let plate_map = plate_map_changed
+                .then(|| render_plate_map(&status))
+                .flatten();
+            let mut replaced = snapshot
+                .iter()
+                .map(|attachment| stem(&attachment.filename))
+                .collect::<Vec<_>>();
+            if plate_map_changed {
+                replaced.push(stem(plate_map::FILE_NAME));
+            }
+
+            let mut builder = EditMessage::from(self.with_snapshot(status.clone()));
+            if !replaced.is_empty() {
+                // Setting any attachment drops the ones that are not explicitly kept
+                for existing in &self.attachments {
+                    if !replaced.contains(&stem(&existing.filename)) {
+                        builder = builder.keep_existing_attachment(existing.id);
+                    }
+                }
+                for attachment in snapshot.iter().chain(&plate_map) {
+                    builder = builder.new_attachment(attachment.clone());
+                }
+            }
+            match retry(|| thread_id.edit_message(ctx, message_id, builder.clone())).await {
+                Ok(message) => {
+                    if plate_map_changed {
+                        self.plate_map =
+                            status.plate_map().cloned().map(|map| (map, Instant::now()));
+                    }
+                    self.attachments = message.attachments;
+                    self.published = Some(status);
+                    self.last_edit = Some(Instant::now());
+                    return Ok(());
+                }
+                Err(err) if is_unknown_message(&err) => {
+                    tracing::warn!("job status message was deleted, sending a new one");
+                }
+                Err(err) => return Err(err),
+            }
+        }
+
+        if snapshot.is_none() {
+            self.snapshot_file_name = None;
+        }
+        let mut builder = CreateMessage::from(self.with_snapshot(status.clone()));
+        for attachment in snapshot.into_iter().chain(render_plate_map(&status)) {
+            builder = builder.add_file(attachment);
+        }
+        let message = retry(|| thread_id.send_message(ctx, builder.clone())).await?;
+        self.plate_map = status.plate_map().cloned().map(|map| (map, Instant::now()));
+        self.message_id = Some(message.id);
+        self.attachments = message.attachments;
+        self.published = Some(status);
+        self.last_edit = Some(Instant::now());
+        Ok(())
+    }
+
+    /// Whether the plate map has to be rendered again: when the objects
+    /// changed, or at most every [`HIGHLIGHT_INTERVAL`] for the current object.
+    fn plate_map_due(&self, status: &JobStatusMessage) -> bool {
+        match (&self.plate_map, status.plate_map()) {
+            (None, None) => false,
+            (Some((shown, rendered_at)), Some(plate_map)) if shown.same_layout(plate_map) => {
+                shown != plate_map && *rendered_at + HIGHLIGHT_INTERVAL <= Instant::now()
+            }
+            _ => true,
+        }
+    }
+
+    fn with_snapshot(&self, status: JobStatusMessage) -> JobStatusMessage {
+        match &self.snapshot_file_name {
+            Some(file_name) => status.snapshot(file_name),
+            None => status,
+        }
+    }
+}
+
+fn render_plate_map(status: &JobStatusMessage) -> Option<CreateAttachment> {
+    match status.plate_map()?.render() {
+        Ok(png) => Some(CreateAttachment::bytes(png, plate_map::FILE_NAME)),
+        Err(err) => {
+            tracing::error!("error rendering plate map: {:?}", err);
+            None
+        }
+    }
+}
+
+/// The file name without its extension, identifying what an attachment shows.
+fn stem(file_name: &str) -> &str {
+    file_name.split('.').next().unwrap_or(file_name)
+}
@@ -0,0 +1,156 @@
+use std::io::Cursor;
+
+use anyhow::Result;
+use image::{ImageFormat, Rgba, RgbaImage};
+
+use crate::moonraker::{BedArea, JobInfo, ObjectInformation};
+
+pub const FILE_NAME: &str = "plate_map.png";
+
+const SIZE: u32 = 400;
+const MARGIN: f64 = 5.0;
+const BACKGROUND: Rgba<u8> = Rgba([30, 31, 34, 255]);
+const BED: Rgba<u8> = Rgba([49, 51, 56, 255]);
+const OBJECT: Rgba<u8> = Rgba([88, 101, 242, 255]);
+const CURRENT: Rgba<u8> = Rgba([240, 178, 50, 255]);
+const EXCLUDED: Rgba<u8> = Rgba([100, 102, 108, 255]);
+const OUTLINE: Rgba<u8> = Rgba([220, 221, 222, 255]);
+
+/// A top-down map of the objects on the build plate.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlateMap {
+    objects: Vec<ObjectInformation>,
+    current_object: Option<String>,
+    bed: Option<BedArea>,
+}
+
+impl PlateMap {
+    /// Returns `None` if Klipper did not report any object outlines.
+    pub fn from_job(job: &JobInfo) -> Option<Self> {
+        let objects = job
+            .objects
+            .iter()
+            .filter(|object| object.polygon.len() >= 3)
+            .cloned()
+            .collect::<Vec<_>>();
+        if objects.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            objects,
+            current_object: job.current_object.clone(),
+            bed: job.bed,
+        })
+    }
+
+    /// Whether both maps show the same objects, regardless of the current one.
+    pub fn same_layout(&self, other: &Self) -> bool {
+        self.objects == other.objects && self.bed == other.bed
+    }
+
+    /// Renders the map to a PNG, with excluded objects greyed out and the
+    /// current object highlighted.
+    pub fn render(&self) -> Result<Vec<u8>> {
+        let area = self.area();
+        let scale = SIZE as f64 / (area.max[0] - area.min[0]).max(area.max[1] - area.min[1]);
+        let width = ((area.max[0] - area.min[0]) * scale).ceil().max(1.0) as u32;
+        let height = ((area.max[1] - area.min[1]) * scale).ceil().max(1.0) as u32;
+        // Image rows grow downwards, bed Y grows away from the front
+        let to_pixel = |[x, y]: [f64; 2]| {
+            [
+                (x - area.min[0]) * scale,
+                height as f64 - (y - area.min[1]) * scale,
+            ]
+        };
+
+        let mut image = RgbaImage::from_pixel(width, height, BACKGROUND);
+        if let Some(bed) = self.bed {
+            let [left, bottom] = to_pixel(bed.min);
+            let [right, top] = to_pixel(bed.max);
+            fill_polygon(
+                &mut image,
+                &[[left, top], [right, top], [right, bottom], [left, bottom]],
+                BED,
+            );
+        }
+
+        for object in &self.objects {
+            let polygon = object
+                .polygon
+                .iter()
+                .map(|point| to_pixel(*point))
+                .collect::<Vec<_>>();
+            let current = self.current_object.as_ref() == Some(&object.name);
+            let color = match (object.excluded, current) {
+                (true, _) => EXCLUDED,
+                (false, true) => CURRENT,
+                (false, false) => OBJECT,
+            };
+            fill_polygon(&mut image, &polygon, color);
+            for (index, from) in polygon.iter().enumerate() {
+                draw_line(
+                    &mut image,
+                    *from,
+                    polygon[(index + 1) % polygon.len()],
+                    OUTLINE,
+                );
+            }
+            if let Some(center) = object.center.filter(|_| current) {
+                let [x, y] = to_pixel(center);
+                draw_line(&mut image, [x - 4.0, y], [x + 4.0, y], OUTLINE);
+                draw_line(&mut image, [x, y - 4.0], [x, y + 4.0], OUTLINE);
+            }
+        }
+
+        let mut png = Vec::new();
+        image.write_to(&mut Cursor::new(&mut png), ImageFormat::Png)?;
+        Ok(png)
+    }
+
+    /// The bed area, grown to fit objects outside of it.
+    fn area(&self) -> BedArea {
+        let points = self.objects.iter().flat_map(|object| &object.polygon);
+        let mut area = self.bed.unwrap_or(BedArea {
+            min: [f64::MAX, f64::MAX],
+            max: [f64::MIN, f64::MIN],
+        });
+        for [x, y] in points {
+            area.min = [area.min[0].min(x - MARGIN), area.min[1].min(y - MARGIN)];
+            area.max = [area.max[0].max(x + MARGIN), area.max[1].max(y + MARGIN)];
+        }
+        area
+    }
+}
+
+/// Fills a polygon using the even-odd rule, sampling at pixel centers.
+fn fill_polygon(image: &mut RgbaImage, polygon: &[[f64; 2]], color: Rgba<u8>) {
+    for row in 0..image.height() {
+        let y = row as f64 + 0.5;
+        let mut crossings = polygon
+            .iter()
+            .zip(polygon.iter().cycle().skip(1))
+            .filter(|([_, y1], [_, y2])| (*y1 <= y) != (*y2 <= y))
+            .map(|([x1, y1], [x2, y2])| x1 + (y - y1) / (y2 - y1) * (x2 - x1))
+            .collect::<Vec<_>>();
+        crossings.sort_by(f64::total_cmp);
+        for span in crossings.chunks_exact(2) {
+            let start = (span[0] - 0.5).ceil().max(0.0) as u32;
+            let end = ((span[1] - 0.5).floor() + 1.0).clamp(0.0, image.width() as f64) as u32;
+            for column in start..end {
+                image.put_pixel(column, row, color);
+            }
+        }
+    }
+}
+
+fn draw_line(image: &mut RgbaImage, [x1, y1]: [f64; 2], [x2, y2]: [f64; 2], color: Rgba<u8>) {
+    let steps = (x2 - x1).abs().max((y2 - y1).abs()).ceil().max(1.0) as u32;
+    for step in 0..=steps {
+        let t = step as f64 / steps as f64;
+        let (x, y) = (x1 + (x2 - x1) * t, y1 + (y2 - y1) * t);
+        if x >= 0.0 && y >= 0.0 && x < image.width() as f64 && y < image.height() as f64 {
+            image.put_pixel(x as u32, y as u32, color);
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use tokio::time::Instant;
+
+use super::AutoPowerOffConfig;
+
+/// How long to wait before checking the extruder temperature again.
+const RECHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// A power-off scheduled after a job completed. The "keep on" button cancels
+/// it by clearing the shared id.
+pub struct PowerOff {
+    pub config: AutoPowerOffConfig,
+    pending: Arc<AtomicU64>,
+    id: u64,
+    deadline: Option<Instant>,
+}
+
+impl PowerOff {
+    pub fn new(config: AutoPowerOffConfig, pending: Arc<AtomicU64>) -> Self {
+        Self {
+            config,
+            pending,
+            id: 0,
+            deadline: None,
+        }
+    }
+
+    /// Schedules the power-off after the idle delay, returning its id.
+    pub fn schedule(&mut self) -> u64 {
+        // Zero means nothing is pending
+        self.id = self.id.wrapping_add(1).max(1);
+        self.pending.store(self.id, Ordering::Relaxed);
+        self.deadline = Some(Instant::now() + Duration::from_secs(self.config.delay));
+        self.id
+    }
+
+    pub fn cancel(&mut self) {
+        _ = self
+            .pending
+            .compare_exchange(self.id, 0, Ordering::Relaxed, Ordering::Relaxed);
+        self.deadline = None;
+    }
+
+    /// Waits another interval for the extruder to cool down.
+    pub fn postpone(&mut self) {
+        self.deadline = Some(Instant::now() + RECHECK_INTERVAL);
+    }
+
+    /// When to switch off, unless it has been cancelled.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+            .filter(|_| self.pending.load(Ordering::Relaxed) == self.id)
+    }
+
+    pub fn is_cool(&self, extruder_temperature: Option<f64>) -> bool {
+        extruder_temperature.is_some_and(|temperature| temperature < self.config.max_temperature)
+    }
+}
+
+/// E.g. `45 seconds`, `10 minutes` or `1 minute 30 seconds`.
+pub fn format_delay(seconds: u64) -> String {
+    let count = |count: u64, unit: &str| match count {
+        1 => format!("1 {}", unit),
+        count => format!("{} {}s", count, unit),
+    };
+    match (seconds / 60, seconds % 60) {
+        (0, seconds) => count(seconds, "second"),
+        (minutes, 0) => count(minutes, "minute"),
+        (minutes, seconds) => format!("{} {}", count(minutes, "minute"), count(seconds, "second")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn power_off() -> PowerOff {
+        let config = AutoPowerOffConfig {
+            device: "printer".to_string(),
+            max_temperature: 50.0,
+            delay: 600,
+        };
+        PowerOff::new(config, Arc::new(AtomicU64::new(0)))
+    }
+
+    /// What the "keep on" button does.
+    fn keep_on(pending: &AtomicU64, id: u64) -> bool {
+        pending
+            .compare_exchange(id, 0, Ordering::Relaxed, Ordering::Relaxed)
+            .is_ok()
+    }
+
+    #[test]
+    fn keep_on_cancels_scheduled_power_off() {
+        let mut power_off = power_off();
+        let id = power_off.schedule();
+        assert!(power_off.deadline().is_some());
+
+        assert!(keep_on(&power_off.pending, id));
+        // The deadline fires without being re-read
+        assert!(power_off.deadline().is_none());
+        power_off.postpone();
+        assert!(power_off.deadline().is_none());
+    }
+
+    #[test]
+    fn keep_on_ignores_previous_schedules() {
+        let mut power_off = power_off();
+        let previous = power_off.schedule();
+        power_off.cancel();
+        power_off.schedule();
+
+        assert!(!keep_on(&power_off.pending, previous));
+        assert!(power_off.deadline().is_some());
+    }
+
+    #[test]
+    fn format_delay_from_seconds() {
+        assert_eq!(format_delay(45), "45 seconds");
+        assert_eq!(format_delay(60), "1 minute");
+        assert_eq!(format_delay(90), "1 minute 30 seconds");
+        assert_eq!(format_delay(600), "10 minutes");
+    }
+}
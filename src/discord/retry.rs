@@ -0,0 +1,61 @@
+use std::{future::Future, time::Duration};
+
+use serenity::{http::HttpError, Error};
+use tokio::time;
+
+const ATTEMPTS: u32 = 4;
+const INITIAL_DELAY: Duration = Duration::from_secs(1);
+
+const UNKNOWN_CHANNEL: isize = 10003;
+const UNKNOWN_MESSAGE: isize = 10008;
+
+/// Runs a Discord request, retrying with exponential backoff while it fails
+/// with a transient error.
+pub async fn retry<T, F, Fut>(mut request: F) -> serenity::Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = serenity::Result<T>>,
+{
+    let mut delay = INITIAL_DELAY;
+    let mut attempt = 1;
+    loop {
+        match request().await {
+            Err(err) if attempt < ATTEMPTS && is_transient(&err) => {
+                tracing::warn!(
+                    "Discord request attempt {} failed, retrying in {:?}: {:?}",
+                    attempt,
+                    delay,
+                    err
+                );
+                time::sleep(delay).await;
+                delay *= 2;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+fn is_transient(err: &Error) -> bool {
+    match err {
+        Error::Http(HttpError::UnsuccessfulRequest(response)) => {
+            response.status_code.is_server_error() || response.status_code.as_u16() == 429
+        }
+        Error::Http(HttpError::Request(_)) => true,
+        _ => false,
+    }
+}
+
+fn has_json_code(err: &Error, code: isize) -> bool {
+    matches!(err, Error::Http(HttpError::UnsuccessfulRequest(response)) if response.error.code == code)
+}
+
+/// Whether the request failed because the channel or thread was deleted.
+pub fn is_unknown_channel(err: &Error) -> bool {
+    has_json_code(err, UNKNOWN_CHANNEL)
+}
+
+/// Whether the request failed because the message was deleted.
+pub fn is_unknown_message(err: &Error) -> bool {
+    has_json_code(err, UNKNOWN_MESSAGE)
+}
@@ -0,0 +1,107 @@
+use std::io::Cursor;
+
+use anyhow::Result;
+use image::{
+    codecs::gif::{GifEncoder, Repeat},
+    imageops::FilterType,
+    Delay, Frame,
+};
+
+use crate::moonraker::webcam::Image;
+
+const FRAME_DELAY_MS: u32 = 100;
+const MAX_WIDTH: u32 = 640;
+const MIN_WIDTH: u32 = 160;
+const MIN_FRAMES: usize = 2;
+/// Most snapshots kept in memory for a job.
+const MAX_FRAMES: usize = 200;
+
+/// Snapshots collected during a job, rendered to an animated GIF at the end.
+#[derive(Default)]
+pub struct Timelapse {
+    frames: Vec<Image>,
+    /// Snapshots received so far, kept or not.
+    received: usize,
+    /// How often the frames were thinned out. Only every `2^thinned`th
+    /// snapshot is kept.
+    thinned: u32,
+}
+
+impl Timelapse {
+    /// Adds a snapshot, halving the frame rate whenever [`MAX_FRAMES`] is
+    /// reached, so long jobs are covered evenly.
+    pub fn push(&mut self, frame: Image) {
+        if self.received.is_multiple_of(1 << self.thinned) {
+            self.frames.push(frame);
+        }
+        self.received += 1;
+
+        if self.frames.len() >= MAX_FRAMES {
+            let mut index = 0usize;
+            self.frames.retain(|_| {
+                index += 1;
+                !index.is_multiple_of(2)
+            });
+            self.thinned += 1;
+            tracing::debug!("thinned timelapse to {} frames", self.frames.len());
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Renders the collected frames to a GIF no larger than `size_limit` bytes,
+    /// dropping frames and downscaling until it fits.
+    pub async fn render(self, size_limit: u64) -> Result<Option<Vec<u8>>> {
+        tokio::task::spawn_blocking(move || render(&self.frames, size_limit)).await?
+    }
+}
+
+fn render(frames: &[Image], size_limit: u64) -> Result<Option<Vec<u8>>> {
+    let mut step = 1;
+    let mut width = MAX_WIDTH;
+    loop {
+        let selected = frames.iter().step_by(step).collect::<Vec<_>>();
+        if selected.len() < MIN_FRAMES || width < MIN_WIDTH {
+            return Ok(None);
+        }
+
+        let gif = encode(&selected, width)?;
+        tracing::debug!(
+            "rendered timelapse with {} frames at {}px: {} bytes",
+            selected.len(),
+            width,
+            gif.len()
+        );
+        if gif.len() as u64 <= size_limit {
+            return Ok(Some(gif));
+        }
+
+        step *= 2;
+        width = width * 3 / 4;
+    }
+}
+
+fn encode(frames: &[&Image], width: u32) -> Result<Vec<u8>> {
+    let mut gif = Vec::new();
+    {
+        let mut encoder = GifEncoder::new_with_speed(Cursor::new(&mut gif), 10);
+        encoder.set_repeat(Repeat::Infinite)?;
+        for frame in frames {
+            let image = image::load_from_memory(&frame.data)?;
+            let image = if image.width() > width {
+                image.resize(width, u32::MAX, FilterType::Triangle)
+            } else {
+                image
+            };
+            encoder.encode_frame(Frame::from_parts(
+                image.to_rgba8(),
+                0,
+                0,
+                Delay::from_numer_denom_ms(FRAME_DELAY_MS, 1),
+            ))?;
+        }
+    }
+    Ok(gif)
+}
@@ -1,21 +1,28 @@
-use std::sync::Arc;
+use std::sync::{atomic::AtomicU64, Arc};
 
 use serenity::{
     all::{ChannelId, UserId},
     prelude::TypeMapKey,
 };
-use tokio::sync::{mpsc, watch, Mutex};
+use tokio::sync::{broadcast, mpsc, watch, Mutex};
 
-use crate::moonraker::{Notification, Status};
+use super::Config;
+use crate::moonraker::{self, Event, Status};
 
 pub struct StatusChannel;
 impl TypeMapKey for StatusChannel {
     type Value = Arc<Mutex<watch::Receiver<Status>>>;
 }
 
-pub struct NotificationChannel;
-impl TypeMapKey for NotificationChannel {
-    type Value = Arc<Mutex<mpsc::Receiver<Notification>>>;
+/// The latest status, for command handlers. The run loop owns [`StatusChannel`].
+pub struct CurrentStatus;
+impl TypeMapKey for CurrentStatus {
+    type Value = watch::Receiver<Status>;
+}
+
+pub struct EventChannel;
+impl TypeMapKey for EventChannel {
+    type Value = Arc<Mutex<mpsc::Receiver<Event>>>;
 }
 
 pub struct OwnerId;
@@ -27,3 +34,24 @@ pub struct PrintsChannel;
 impl TypeMapKey for PrintsChannel {
     type Value = Arc<ChannelId>;
 }
+
+pub struct DiscordConfig;
+impl TypeMapKey for DiscordConfig {
+    type Value = Arc<Config>;
+}
+
+pub struct Moonraker;
+impl TypeMapKey for Moonraker {
+    type Value = Arc<moonraker::Client>;
+}
+
+pub struct Console;
+impl TypeMapKey for Console {
+    type Value = broadcast::Sender<String>;
+}
+
+/// Id of the scheduled power-off, zero if none is pending.
+pub struct PendingPowerOff;
+impl TypeMapKey for PendingPowerOff {
+    type Value = Arc<AtomicU64>;
+}
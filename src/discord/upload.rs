@@ -0,0 +1,29 @@
+use anyhow::Result;
+use serenity::all::{Attachment, Context, CreateActionRow};
+
+use super::{components, typemap::Moonraker};
+
+/// Whether the attachment looks like a G-code file.
+pub fn is_gcode(attachment: &Attachment) -> bool {
+    attachment.filename.to_lowercase().ends_with(".gcode")
+}
+
+/// Uploads a G-code attachment to the printer, returning the reply offering
+/// to print or queue it.
+pub async fn upload_gcode(
+    ctx: &Context,
+    attachment: &Attachment,
+) -> Result<(String, Vec<CreateActionRow>)> {
+    let client = {
+        let data_read = ctx.data.read().await;
+        data_read.get::<Moonraker>().unwrap().clone()
+    };
+
+    let data = attachment.download().await?;
+    let path = client.upload_gcode(&attachment.filename, data).await?;
+    tracing::info!("uploaded {:?}", path);
+    Ok((
+        format!("Uploaded {}", path),
+        components::upload_buttons(&path),
+    ))
+}
@@ -1,4 +1,4 @@
-use tokio::sync::{mpsc, watch};
+use tokio::sync::{broadcast, mpsc, watch};
 use tracing::Level;
 use tracing_subscriber::{util::SubscriberInitExt, EnvFilter};
 
@@ -16,17 +16,23 @@ async fn main() -> anyhow::Result<()> {
         .try_init()?;
 
     let (status_tx, status_rx) = watch::channel(moonraker::Status::default());
-    let (notification_tx, notification_rx) = mpsc::channel(10);
+    let (event_tx, event_rx) = mpsc::channel(10);
+    let (console_tx, _) = broadcast::channel(100);
 
     let moon = moonraker::Service::builder(conf.moonraker).await?;
+    let moonraker_client = moon.client();
+    let moonraker_console_tx = console_tx.clone();
     tokio::spawn(async move {
-        if let Err(err) = moon.start(status_tx, notification_tx).await {
+        if let Err(err) = moon.start(status_tx, event_tx, moonraker_console_tx).await {
             tracing::error!("Moonraker error: {:?}", err);
         }
     });
 
     let discord = discord::Service::builder(conf.discord).await?;
-    if let Err(err) = discord.start(status_rx, notification_rx).await {
+    if let Err(err) = discord
+        .start(status_rx, event_rx, console_tx, moonraker_client)
+        .await
+    {
         tracing::error!("Discord error: {:?}", err);
     }
 
@@ -1,31 +1,63 @@
 use std::{
-    fs::File,
+    collections::HashSet,
     future::{Future, IntoFuture},
     pin::Pin,
     sync::Arc,
+    time::Duration,
 };
 
 use anyhow::Result;
+use serde_json::Value;
 use tokio::{
     select,
-    sync::{mpsc, watch},
+    sync::{broadcast, mpsc, watch},
+    task::JoinHandle,
+    time,
 };
 
-use self::client::Client;
-pub use self::status::*;
+use self::api::{
+    AnnouncementEntry, FileMetadata, PrinterObjectStatus, TimelapseEvent, UpdateStatusResponse,
+    VersionInformation,
+};
+use self::console::ConsoleWatcher;
+pub use self::host::HostConfig;
+use self::host::HostMonitor;
+pub use self::{client::Client, status::*};
 
 mod api;
 mod client;
 mod client_builder;
+mod console;
+mod host;
 mod status;
-mod webcam;
+pub mod webcam;
 
 const NOTIFICATION_METHOD: &str = "rusty_moon_notification";
+const DISK_CHECK_INTERVAL: Duration = Duration::from_secs(5 * 60);
+/// Database key of the announcements that have been posted to Discord.
+const POSTED_ANNOUNCEMENTS_KEY: &str = "posted_announcements";
 
 #[derive(Debug, Clone, serde::Deserialize)]
 struct NotificationParams {
     pub message: String,
-    pub webcam: Option<String>,
+    pub webcam: Option<Webcams>,
+}
+
+/// One or more webcam names, e.g. `webcam="nozzle"` or `webcam=["nozzle", "overview"]`.
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(untagged)]
+enum Webcams {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl From<Webcams> for Vec<String> {
+    fn from(value: Webcams) -> Self {
+        match value {
+            Webcams::One(webcam) => vec![webcam],
+            Webcams::Many(webcams) => webcams,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Default)]
@@ -36,16 +68,92 @@ pub enum KlippyState {
     Shutdown,
 }
 
+#[derive(Debug)]
+pub enum Event {
+    Notification(Notification),
+    Snapshot(Snapshot),
+    TimelapseRender(TimelapseRender),
+    ConsoleError(ConsoleError),
+    UpdatesAvailable(Vec<AvailableUpdate>),
+    Announcement(Announcement),
+    HostAlert(String),
+}
+
+/// A notice from the Moonraker developers, e.g. about security or breaking changes.
+#[derive(Clone, Debug)]
+pub struct Announcement {
+    pub entry_id: String,
+    pub title: String,
+    pub description: String,
+    pub url: String,
+    pub priority: String,
+}
+
+/// A component of the update manager with an update available.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AvailableUpdate {
+    pub name: String,
+    /// What the update changes, e.g. the versions or number of packages.
+    pub description: String,
+}
+
 #[derive(Debug, Default)]
 pub struct Notification {
     pub message: String,
-    pub image: Option<File>,
+    pub images: Vec<Snapshot>,
+    /// Webcams that failed to take a snapshot.
+    pub unavailable_webcams: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct Snapshot {
+    pub webcam: String,
+    pub image: webcam::Image,
+}
+
+/// A moonraker-timelapse video that has finished rendering.
+#[derive(Debug)]
+pub struct TimelapseRender {
+    /// Path in Moonraker's timelapse root.
+    pub file_name: String,
+    /// Link to the video for Discord users.
+    pub url: String,
+}
+
+/// A console line reporting an error, with the lines around it.
+#[derive(Debug)]
+pub struct ConsoleError {
+    pub line: String,
+    pub context: Vec<String>,
+    /// Position of the error line in the context.
+    pub error_index: usize,
 }
 
 #[derive(Debug, Clone, serde::Deserialize)]
 pub struct Config {
     pub host: String,
     pub port: Option<u16>,
+    pub default_webcam: Option<String>,
+    /// Base URL under which Discord users reach Moonraker, e.g. behind a
+    /// reverse proxy. Links to files fall back to the host and port.
+    pub public_url: Option<String>,
+    pub snapshot: Option<SnapshotConfig>,
+    /// Regexes of console lines to report as errors, besides those starting with `!!`.
+    #[serde(default)]
+    pub console_errors: Vec<String>,
+    #[serde(default)]
+    pub host_alerts: HostConfig,
+}
+
+/// Periodic webcam snapshots taken while printing.
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct SnapshotConfig {
+    /// Falls back to the default webcam.
+    pub webcam: Option<String>,
+    /// Seconds between snapshots.
+    pub interval: Option<u64>,
+    /// Layers between snapshots.
+    pub layers: Option<u16>,
 }
 
 pub struct ServiceBuilder {
@@ -64,15 +172,46 @@ impl IntoFuture for ServiceBuilder {
 
     fn into_future(self) -> Self::IntoFuture {
         Box::pin(async move {
+            let snapshot = self.config.snapshot.clone();
+            let console = ConsoleWatcher::new(&self.config.console_errors)?;
+            let host_monitor = HostMonitor::new(self.config.host_alerts.clone());
             let client = Arc::new(Client::builder(self.config).await?);
 
-            Ok(Service { client })
+            Ok(Service {
+                client,
+                printer_status: Value::Null,
+                metadata: None,
+                snapshot,
+                snapshot_layer: None,
+                snapshot_task: None,
+                layer_tracker: LayerTracker::default(),
+                console,
+                queue: JobQueue::default(),
+                power_devices: Vec::new(),
+                announced_updates: Vec::new(),
+                posted_announcements: HashSet::new(),
+                host: HostStatus::default(),
+                host_monitor,
+            })
         })
     }
 }
 
 pub struct Service {
     client: Arc<Client>,
+    printer_status: Value,
+    metadata: Option<FileMetadata>,
+    snapshot: Option<SnapshotConfig>,
+    snapshot_layer: Option<u16>,
+    snapshot_task: Option<JoinHandle<()>>,
+    layer_tracker: LayerTracker,
+    console: ConsoleWatcher,
+    queue: JobQueue,
+    power_devices: Vec<PowerDevice>,
+    announced_updates: Vec<AvailableUpdate>,
+    posted_announcements: HashSet<String>,
+    host: HostStatus,
+    host_monitor: HostMonitor,
 }
 
 impl Service {
@@ -80,10 +219,15 @@ impl Service {
         ServiceBuilder::new(config)
     }
 
+    pub fn client(&self) -> Arc<Client> {
+        Arc::clone(&self.client)
+    }
+
     pub async fn start(
-        self,
+        mut self,
         status_tx: watch::Sender<Status>,
-        notification_tx: mpsc::Sender<Notification>,
+        event_tx: mpsc::Sender<Event>,
+        console_tx: broadcast::Sender<String>,
     ) -> Result<()> {
         self.client.identify().await?;
 
@@ -98,15 +242,63 @@ impl Service {
         let mut ready_sub = self.client.subscribe_klippy_ready().await?;
         let mut disconnected_sub = self.client.subscribe_klippy_disconnected().await?;
         let mut shutdown_sub = self.client.subscribe_klippy_shutdown().await?;
+        let mut timelapse_sub = self.client.subscribe_timelapse_event().await?;
+        let mut gcode_response_sub = self.client.subscribe_gcode_response().await?;
+        let mut job_queue_sub = self.client.subscribe_job_queue_changed().await?;
+        let mut power_sub = self.client.subscribe_power_changed().await?;
+        let mut update_sub = self.client.subscribe_update_refreshed().await?;
+        let mut announcement_sub = self.client.subscribe_announcement_update().await?;
+        let mut proc_stat_sub = self.client.subscribe_proc_stat_update().await?;
+        let mut throttled_sub = self.client.subscribe_cpu_throttled().await?;
+        let mut disk_timer = time::interval(DISK_CHECK_INTERVAL);
+
+        let snapshot_period = self
+            .snapshot
+            .as_ref()
+            .and_then(|snapshot| snapshot.interval)
+            .filter(|interval| *interval > 0)
+            .map(Duration::from_secs);
+        let mut snapshot_timer = time::interval(snapshot_period.unwrap_or(Duration::from_secs(60)));
+        snapshot_timer.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
 
+        self.update_job_queue(&status_tx).await;
+        self.update_power_devices(&status_tx).await;
+        match self.client.get_update_status().await {
+            Ok(status) => self.announce_updates(status, &event_tx).await?,
+            Err(err) => tracing::warn!("error reading update status: {:?}", err),
+        }
+        match self.client.get_proc_stats().await {
+            Ok(stats) => {
+                self.update_host(|host| host.update(stats), &status_tx, &event_tx)
+                    .await?
+            }
+            Err(err) => tracing::warn!("error reading host statistics: {:?}", err),
+        }
+        match self
+            .client
+            .get_database_item::<Vec<String>>(POSTED_ANNOUNCEMENTS_KEY)
+            .await
+        {
+            Ok(posted) => self.posted_announcements.extend(posted.unwrap_or_default()),
+            Err(err) => tracing::warn!("error reading posted announcements: {:?}", err),
+        }
+        match self.client.list_announcements().await {
+            Ok(entries) => self.post_announcements(entries, &event_tx).await?,
+            Err(err) => tracing::warn!("error reading announcements: {:?}", err),
+        }
         self.update_klippy_status(self.get_initial_klippy_state().await?, &status_tx)
             .await?;
         loop {
+            let console_deadline = self.console.deadline();
             // TODO: handle errors
             select! {
                 Some(res) = status_sub.next() => match res {
                     Ok(status) => {
-                        status_tx.send_replace(Status::from(&status.status));
+                        self.update_printer_status(status.status, &status_tx).await;
+                        let status = status_tx.borrow().clone();
+                        if self.layer_snapshot_due(&status) {
+                            self.send_snapshot(&event_tx);
+                        }
                     },
                     Err(err) => tracing::error!("error reading status subscription: {:?}", err),
                 },
@@ -123,35 +315,282 @@ impl Service {
                     Err(err) => tracing::error!("error reading shutdown subscription: {:?}", err),
                 },
                 Some(notification) = notification_sub.next() => match notification {
-                    Ok(notification) => self.handle_notification(notification, &notification_tx).await?,
+                    Ok(notification) => self.handle_notification(notification, &event_tx),
                     Err(err) => tracing::error!("error reading notification: {:?}", err),
                 },
+                Some(res) = timelapse_sub.next() => match res {
+                    Ok((event,)) => self.handle_timelapse_event(event, &event_tx).await?,
+                    Err(err) => tracing::error!("error reading timelapse subscription: {:?}", err),
+                },
+                Some(res) = gcode_response_sub.next() => match res {
+                    Ok((line,)) => self.handle_console_line(line, &event_tx, &console_tx).await?,
+                    Err(err) => tracing::error!("error reading gcode response subscription: {:?}", err),
+                },
+                Some(res) = job_queue_sub.next() => match res {
+                    Ok(_) => self.update_job_queue(&status_tx).await,
+                    Err(err) => tracing::error!("error reading job queue subscription: {:?}", err),
+                },
+                Some(res) = power_sub.next() => match res {
+                    Ok((device,)) => self.update_power_device(device.into(), &status_tx),
+                    Err(err) => tracing::error!("error reading power subscription: {:?}", err),
+                },
+                Some(res) = update_sub.next() => match res {
+                    Ok((status,)) => self.announce_updates(status, &event_tx).await?,
+                    Err(err) => tracing::error!("error reading update subscription: {:?}", err),
+                },
+                Some(res) = announcement_sub.next() => match res {
+                    Ok((response,)) => self.post_announcements(response.entries, &event_tx).await?,
+                    Err(err) => tracing::error!("error reading announcement subscription: {:?}", err),
+                },
+                Some(res) = proc_stat_sub.next() => match res {
+                    Ok((stats,)) => self.update_host(|host| host.update(stats), &status_tx, &event_tx).await?,
+                    Err(err) => tracing::error!("error reading proc stat subscription: {:?}", err),
+                },
+                Some(res) = throttled_sub.next() => match res {
+                    Ok((state,)) => self.update_host(|host| host.throttled = state.flags, &status_tx, &event_tx).await?,
+                    Err(err) => tracing::error!("error reading throttled subscription: {:?}", err),
+                },
+                _ = disk_timer.tick() => match self.client.get_disk_usage("gcodes").await {
+                    Ok(usage) => self.update_host(|host| host.update_disk(usage), &status_tx, &event_tx).await?,
+                    Err(err) => tracing::warn!("error reading disk usage: {:?}", err),
+                },
+                _ = time::sleep_until(console_deadline.unwrap_or_else(time::Instant::now)), if console_deadline.is_some() => {
+                    if let Some(error) = self.console.take_pending() {
+                        event_tx.send(Event::ConsoleError(error)).await?;
+                    }
+                },
+                _ = snapshot_timer.tick(), if snapshot_period.is_some() => {
+                    if status_tx.borrow().state == State::Printing {
+                        self.send_snapshot(&event_tx);
+                    }
+                },
             }
         }
     }
 
-    async fn handle_notification(
-        &self,
-        params: NotificationParams,
-        notification_tx: &mpsc::Sender<Notification>,
-    ) -> Result<()> {
+    /// Takes the snapshots in a separate task, so slow webcams do not hold up
+    /// the other subscriptions.
+    fn handle_notification(&self, params: NotificationParams, event_tx: &mpsc::Sender<Event>) {
         tracing::info!("received notification: {:?}", params);
-        let image = match params.webcam {
-            Some(webcam) => webcam::get_webcam_snapshot(&self.client, webcam).await?,
-            None => None,
-        };
+        let client = self.client();
+        let event_tx = event_tx.clone();
+        tokio::spawn(async move {
+            let mut images = Vec::new();
+            let mut unavailable_webcams = Vec::new();
+            for webcam in params.webcam.map(Vec::from).unwrap_or_default() {
+                match webcam::get_webcam_snapshot(&client, &webcam).await {
+                    Ok(image) => images.push(Snapshot { webcam, image }),
+                    Err(err) => {
+                        tracing::error!("error taking snapshot with {:?}: {:?}", webcam, err);
+                        unavailable_webcams.push(webcam);
+                    }
+                }
+            }
 
-        notification_tx
-            .send(Notification {
+            let notification = Notification {
                 message: params.message,
-                image,
+                images,
+                unavailable_webcams,
+            };
+            if let Err(err) = event_tx.send(Event::Notification(notification)).await {
+                tracing::error!("error sending notification: {:?}", err);
+            }
+        });
+    }
+
+    async fn handle_console_line(
+        &mut self,
+        line: String,
+        event_tx: &mpsc::Sender<Event>,
+        console_tx: &broadcast::Sender<String>,
+    ) -> Result<()> {
+        // Nobody listening to the console is not an error
+        _ = console_tx.send(line.clone());
+        if let Some(error) = self.console.push(line) {
+            tracing::warn!("console error: {:?}", error.line);
+            event_tx.send(Event::ConsoleError(error)).await?;
+        }
+        Ok(())
+    }
+
+    /// Sends the updates that have not been announced yet.
+    async fn announce_updates(
+        &mut self,
+        status: UpdateStatusResponse,
+        event_tx: &mpsc::Sender<Event>,
+    ) -> Result<()> {
+        let mut available = status
+            .version_info
+            .into_iter()
+            .filter_map(|(name, info)| {
+                let description = describe_update(info)?;
+                Some(AvailableUpdate { name, description })
             })
+            .collect::<Vec<_>>();
+        available.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let new = available
+            .iter()
+            .filter(|update| !self.announced_updates.contains(update))
+            .cloned()
+            .collect::<Vec<_>>();
+        self.announced_updates = available;
+        if !new.is_empty() {
+            tracing::info!("updates available: {:?}", new);
+            event_tx.send(Event::UpdatesAvailable(new)).await?;
+        }
+        Ok(())
+    }
+
+    /// Sends the announcements that are neither dismissed nor posted yet, and
+    /// stores which were posted so restarts do not repeat them.
+    async fn post_announcements(
+        &mut self,
+        entries: Vec<AnnouncementEntry>,
+        event_tx: &mpsc::Sender<Event>,
+    ) -> Result<()> {
+        let current = entries
+            .iter()
+            .map(|entry| entry.entry_id.clone())
+            .collect::<HashSet<_>>();
+        let previously_posted = self.posted_announcements.clone();
+        // Forget the announcements Moonraker no longer lists
+        self.posted_announcements.retain(|id| current.contains(id));
+
+        for entry in entries {
+            if entry.dismissed || !self.posted_announcements.insert(entry.entry_id.clone()) {
+                continue;
+            }
+            tracing::info!("new announcement: {:?}", entry.entry_id);
+            event_tx
+                .send(Event::Announcement(Announcement {
+                    entry_id: entry.entry_id,
+                    title: entry.title,
+                    description: entry.description,
+                    url: entry.url,
+                    priority: entry.priority,
+                }))
+                .await?;
+        }
+
+        if self.posted_announcements != previously_posted {
+            let posted = self.posted_announcements.iter().collect::<Vec<_>>();
+            if let Err(err) = self
+                .client
+                .post_database_item(POSTED_ANNOUNCEMENTS_KEY, posted)
+                .await
+            {
+                tracing::warn!("error storing posted announcements: {:?}", err);
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies a change to the host status, publishes it and raises any alerts.
+    async fn update_host(
+        &mut self,
+        change: impl FnOnce(&mut HostStatus),
+        status_tx: &watch::Sender<Status>,
+        event_tx: &mpsc::Sender<Event>,
+    ) -> Result<()> {
+        change(&mut self.host);
+        status_tx.send_if_modified(|status| {
+            let modified = status.host != self.host;
+            status.host = self.host.clone();
+            modified
+        });
+
+        for alert in self.host_monitor.check(&self.host) {
+            tracing::warn!("host alert: {}", alert);
+            event_tx.send(Event::HostAlert(alert)).await?;
+        }
+        Ok(())
+    }
+
+    async fn handle_timelapse_event(
+        &self,
+        event: TimelapseEvent,
+        event_tx: &mpsc::Sender<Event>,
+    ) -> Result<()> {
+        tracing::debug!("received timelapse event: {:?}", event);
+        if event.action != "render" || event.status.as_deref() != Some("success") {
+            return Ok(());
+        }
+        let Some(file_name) = event.file_name else {
+            return Ok(());
+        };
+
+        let url = self.client.public_file_url("timelapse", &file_name)?;
+        event_tx
+            .send(Event::TimelapseRender(TimelapseRender {
+                file_name,
+                url: url.to_string(),
+            }))
             .await?;
         Ok(())
     }
 
+    fn layer_snapshot_due(&mut self, status: &Status) -> bool {
+        let Some(layers) = self
+            .snapshot
+            .as_ref()
+            .and_then(|snapshot| snapshot.layers)
+            .filter(|layers| *layers > 0)
+        else {
+            return false;
+        };
+        let Some(job) = status
+            .printer
+            .as_ref()
+            .and_then(|printer| printer.job.as_ref())
+        else {
+            return false;
+        };
+        if status.state != State::Printing
+            || job.current_layer == 0
+            || job.current_layer % layers != 0
+            || self.snapshot_layer == Some(job.current_layer)
+        {
+            return false;
+        }
+
+        self.snapshot_layer = Some(job.current_layer);
+        true
+    }
+
+    /// Sends a periodic snapshot of the configured webcam, unless the previous
+    /// one is still being taken.
+    fn send_snapshot(&mut self, event_tx: &mpsc::Sender<Event>) {
+        let Some(snapshot) = &self.snapshot else {
+            return;
+        };
+        if self
+            .snapshot_task
+            .as_ref()
+            .is_some_and(|task| !task.is_finished())
+        {
+            tracing::warn!("previous snapshot still in progress, skipping");
+            return;
+        }
+
+        let client = self.client();
+        let webcam = snapshot.webcam.clone();
+        let event_tx = event_tx.clone();
+        self.snapshot_task = Some(tokio::spawn(async move {
+            match take_snapshot(&client, webcam.as_deref()).await {
+                Ok(Some(snapshot)) => {
+                    if let Err(err) = event_tx.send(Event::Snapshot(snapshot)).await {
+                        tracing::error!("error sending snapshot: {:?}", err);
+                    }
+                }
+                Ok(None) => tracing::warn!("no webcam available for snapshot"),
+                Err(err) => tracing::error!("error taking snapshot: {:?}", err),
+            }
+        }));
+    }
+
     async fn update_klippy_status(
-        &self,
+        &mut self,
         klippy_status: KlippyState,
         status_tx: &watch::Sender<Status>,
     ) -> Result<()> {
@@ -159,12 +598,17 @@ impl Service {
             KlippyState::Ready => {
                 self.register().await?;
                 let status = self.client.get_printer_status().await?;
-                status_tx.send_replace(Status::from(&status.status));
+                self.printer_status = Value::Null;
+                self.update_printer_status(status.status, status_tx).await;
             }
             KlippyState::Disconnected => {
+                self.printer_status = Value::Null;
                 status_tx.send_replace(Status {
                     printer: None,
                     state: State::Disconnected,
+                    queue: self.queue.clone(),
+                    power_devices: self.power_devices.clone(),
+                    host: self.host.clone(),
                 });
             }
             KlippyState::Shutdown => {
@@ -177,6 +621,122 @@ impl Service {
         Ok(())
     }
 
+    /// Merges a partial status update into the printer status and publishes the result.
+    async fn update_printer_status(&mut self, update: Value, status_tx: &watch::Sender<Status>) {
+        merge(&mut self.printer_status, update);
+        match serde_json::from_value::<PrinterObjectStatus>(self.printer_status.clone()) {
+            Ok(status) => self.publish_status(&status, status_tx).await,
+            Err(err) => tracing::error!("error reading printer status: {:?}", err),
+        }
+    }
+
+    async fn publish_status(
+        &mut self,
+        status: &PrinterObjectStatus,
+        status_tx: &watch::Sender<Status>,
+    ) {
+        if let Some(file_name) = status.print_stats.file_name.as_deref() {
+            if !file_name.is_empty()
+                && self
+                    .metadata
+                    .as_ref()
+                    .map(|metadata| metadata.file_name.as_str())
+                    != Some(file_name)
+            {
+                self.snapshot_layer = None;
+                self.layer_tracker = LayerTracker::default();
+                self.metadata = Some(match self.client.get_file_metadata(file_name).await {
+                    Ok(metadata) => metadata,
+                    Err(err) => {
+                        tracing::warn!("error reading metadata for {:?}: {:?}", file_name, err);
+                        FileMetadata {
+                            file_name: file_name.to_string(),
+                            ..Default::default()
+                        }
+                    }
+                });
+            }
+        }
+
+        let mut new_status = Status {
+            queue: self.queue.clone(),
+            power_devices: self.power_devices.clone(),
+            host: self.host.clone(),
+            ..Status::from((status, self.metadata.as_ref()))
+        };
+        let previous_state = status_tx.borrow().state.clone();
+        if new_status.state == State::Printing
+            && !matches!(previous_state, State::Printing | State::Paused)
+        {
+            self.layer_tracker = LayerTracker::default();
+        }
+        if status.print_stats.info.current_layer.is_none() {
+            let printing =
+                new_status.state == State::Printing && !status.timelapse_take_frame.taking_frame;
+            if let Some(job) = new_status
+                .printer
+                .as_mut()
+                .and_then(|printer| printer.job.as_mut())
+            {
+                job.current_layer = self.layer_tracker.update(job.current_layer, printing);
+            }
+        }
+        status_tx.send_replace(new_status);
+    }
+
+    /// Fetches the job queue and publishes it with the current status. Leaves
+    /// the queue empty if Moonraker's job queue is not enabled.
+    async fn update_job_queue(&mut self, status_tx: &watch::Sender<Status>) {
+        match self.client.get_job_queue().await {
+            Ok(queue) => self.queue = queue.into(),
+            Err(err) => {
+                tracing::warn!("error reading job queue: {:?}", err);
+                return;
+            }
+        }
+        status_tx.send_if_modified(|status| {
+            let modified = status.queue != self.queue;
+            status.queue = self.queue.clone();
+            modified
+        });
+    }
+
+    /// Fetches the power devices and publishes them with the current status.
+    /// Leaves them empty if Moonraker has no power devices configured.
+    async fn update_power_devices(&mut self, status_tx: &watch::Sender<Status>) {
+        match self.client.list_power_devices().await {
+            Ok(devices) => {
+                self.power_devices = devices.into_iter().map(PowerDevice::from).collect()
+            }
+            Err(err) => {
+                tracing::warn!("error reading power devices: {:?}", err);
+                return;
+            }
+        }
+        self.publish_power_devices(status_tx);
+    }
+
+    fn update_power_device(&mut self, device: PowerDevice, status_tx: &watch::Sender<Status>) {
+        tracing::debug!("power device changed: {:?}", device);
+        match self
+            .power_devices
+            .iter_mut()
+            .find(|known| known.name == device.name)
+        {
+            Some(known) => *known = device,
+            None => self.power_devices.push(device),
+        }
+        self.publish_power_devices(status_tx);
+    }
+
+    fn publish_power_devices(&self, status_tx: &watch::Sender<Status>) {
+        status_tx.send_if_modified(|status| {
+            let modified = status.power_devices != self.power_devices;
+            status.power_devices = self.power_devices.clone();
+            modified
+        });
+    }
+
     async fn get_initial_klippy_state(&self) -> Result<KlippyState, anyhow::Error> {
         let info = self.client.get_server_info().await?;
         match info.klippy_state.as_str() {
@@ -195,3 +755,109 @@ impl Service {
         Ok(())
     }
 }
+
+/// What an update would change, or `None` if the component is up to date.
+fn describe_update(info: VersionInformation) -> Option<String> {
+    if let Some(count) = info.package_count {
+        return (count > 0).then(|| format!("{} packages", count));
+    }
+    // A dirty or locally ahead checkout reports a different version without
+    // being behind, so git repos are compared by commits instead
+    let git_repo = info.configured_type.as_deref() == Some("git_repo");
+    let behind = info
+        .commits_behind_count
+        .unwrap_or(info.commits_behind.len() as u32);
+    match (info.version, info.remote_version) {
+        _ if git_repo && behind == 0 => None,
+        (Some(version), Some(remote_version)) if git_repo => Some(format!(
+            "{} → {} ({} commits)",
+            version, remote_version, behind
+        )),
+        _ if git_repo => Some(format!("{} commits", behind)),
+        (Some(version), Some(remote_version))
+            if remote_version != "?" && version != remote_version =>
+        {
+            Some(format!("{} → {}", version, remote_version))
+        }
+        _ => None,
+    }
+}
+
+async fn take_snapshot(client: &Client, webcam: Option<&str>) -> Result<Option<Snapshot>> {
+    let Some(webcam) = webcam::resolve_webcam(client, webcam).await? else {
+        return Ok(None);
+    };
+    let image = webcam::get_webcam_snapshot(client, &webcam).await?;
+    Ok(Some(Snapshot { webcam, image }))
+}
+
+/// Recursively merges `patch` into `target`, replacing everything but objects.
+fn merge(target: &mut Value, patch: Value) {
+    match (target, patch) {
+        (Value::Object(target), Value::Object(patch)) => {
+            for (key, value) in patch {
+                merge(target.entry(key).or_insert(Value::Null), value);
+            }
+        }
+        (target, patch) => *target = patch,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn merge_keeps_siblings_of_nested_changes() {
+        let mut status = json!({
+            "print_stats": {"state": "printing", "info": {"current_layer": 1, "total_layer": 10}},
+            "toolhead": {"position": [1.0, 2.0, 0.2, 0.0]},
+        });
+        merge(
+            &mut status,
+            json!({"print_stats": {"info": {"current_layer": 2}}}),
+        );
+        assert_eq!(
+            status,
+            json!({
+                "print_stats": {"state": "printing", "info": {"current_layer": 2, "total_layer": 10}},
+                "toolhead": {"position": [1.0, 2.0, 0.2, 0.0]},
+            })
+        );
+    }
+
+    #[test]
+    fn merge_replaces_arrays_and_nulls() {
+        let mut status = json!({
+            "toolhead": {"position": [1.0, 2.0, 0.2, 0.0]},
+            "exclude_object": {"current_object": "cube"},
+        });
+        merge(
+            &mut status,
+            json!({
+                "toolhead": {"position": [3.0, 4.0]},
+                "exclude_object": {"current_object": null},
+            }),
+        );
+        assert_eq!(
+            status,
+            json!({
+                "toolhead": {"position": [3.0, 4.0]},
+                "exclude_object": {"current_object": null},
+            })
+        );
+    }
+
+    #[test]
+    fn merge_adds_new_fields() {
+        let mut status = Value::Null;
+        merge(&mut status, json!({"extruder": {"temperature": 210.0}}));
+        merge(&mut status, json!({"extruder": {"target": 215.0}}));
+        assert_eq!(
+            status,
+            json!({"extruder": {"temperature": 210.0, "target": 215.0}})
+        );
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use serde::Deserialize;
 
 #[derive(Clone, Debug, Default, Deserialize)]
@@ -20,6 +22,9 @@ pub struct PrintStatsInfo {
 #[derive(Clone, Debug, Default, Deserialize)]
 pub struct Object {
     pub name: String,
+    #[serde(default)]
+    pub polygon: Vec<[f64; 2]>,
+    pub center: Option<[f64; 2]>,
 }
 
 #[derive(Clone, Debug, Default, Deserialize)]
@@ -43,22 +48,52 @@ pub struct PrintStats {
     pub message: Option<String>,
 }
 
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct Toolhead {
+    #[serde(default)]
+    pub position: Vec<f64>,
+    #[serde(default)]
+    pub axis_minimum: Vec<f64>,
+    #[serde(default)]
+    pub axis_maximum: Vec<f64>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct Heater {
+    pub temperature: Option<f64>,
+    pub target: Option<f64>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct TimelapseTakeFrame {
+    #[serde(default, rename = "takingframe")]
+    pub taking_frame: bool,
+}
+
 #[derive(Clone, Debug, Default, Deserialize)]
 pub struct PrinterObjectStatus {
     #[serde(default)]
     pub display_status: DisplayStatus,
     #[serde(default)]
     pub exclude_object: ExcludeObject,
+    pub extruder: Option<Heater>,
+    pub heater_bed: Option<Heater>,
     #[serde(default)]
     pub idle_timeout: IdleTimeout,
     #[serde(default)]
     pub print_stats: PrintStats,
+    #[serde(default)]
+    pub toolhead: Toolhead,
+    #[serde(default, rename = "gcode_macro TIMELAPSE_TAKE_FRAME")]
+    pub timelapse_take_frame: TimelapseTakeFrame,
 }
 
+/// Printer object status as sent by Moonraker. Subscription updates only
+/// contain the fields that changed.
 #[derive(Clone, Debug, Default, Deserialize)]
 pub struct PrinterObjectStatusResponse {
     #[serde(default)]
-    pub status: PrinterObjectStatus,
+    pub status: serde_json::Value,
     #[serde(rename = "eventtime")]
     pub _event_time: f64,
 }
@@ -81,7 +116,187 @@ pub struct WebCamInformationResult {
     pub webcam: WebCamInformation,
 }
 
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct WebCamListResult {
+    #[serde(default)]
+    pub webcams: Vec<WebCamInformation>,
+}
+
 #[derive(Clone, Debug, Default, Deserialize)]
 pub struct WebCamInformation {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
     pub snapshot_url: String,
+    #[serde(default)]
+    pub stream_url: String,
+    #[serde(default)]
+    pub flip_horizontal: bool,
+    #[serde(default)]
+    pub flip_vertical: bool,
+    #[serde(default)]
+    pub rotation: u16,
+    pub aspect_ratio: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct FileMetadata {
+    #[serde(rename = "filename")]
+    pub file_name: String,
+    pub layer_height: Option<f64>,
+    pub first_layer_height: Option<f64>,
+    pub object_height: Option<f64>,
+    pub layer_count: Option<u16>,
+    /// Estimated print time in seconds.
+    pub estimated_time: Option<f64>,
+    #[serde(default)]
+    pub thumbnails: Vec<Thumbnail>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Thumbnail {
+    pub width: u32,
+    pub height: u32,
+    /// Relative to the directory of the G-code file.
+    pub relative_path: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct FileUploadItem {
+    pub path: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct FileUploadResponse {
+    pub item: FileUploadItem,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct QueuedJobInformation {
+    pub job_id: String,
+    #[serde(rename = "filename")]
+    pub file_name: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct JobQueueStatus {
+    #[serde(default)]
+    pub queued_jobs: Vec<QueuedJobInformation>,
+    pub queue_state: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct PowerDeviceInformation {
+    pub device: String,
+    /// One of `on`, `off`, `init` or `error`.
+    pub status: String,
+    #[serde(default)]
+    pub locked_while_printing: bool,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct PowerDeviceListResult {
+    pub devices: Vec<PowerDeviceInformation>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct VersionInformation {
+    /// E.g. `git_repo`, `web` or `zip`.
+    pub configured_type: Option<String>,
+    pub version: Option<String>,
+    pub remote_version: Option<String>,
+    /// Set for the system packages only.
+    pub package_count: Option<u32>,
+    /// Set for git repositories only, older Moonraker versions list the commits instead.
+    pub commits_behind_count: Option<u32>,
+    #[serde(default)]
+    pub commits_behind: Vec<serde_json::Value>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct UpdateStatusResponse {
+    #[serde(default)]
+    pub busy: bool,
+    #[serde(default)]
+    pub version_info: HashMap<String, VersionInformation>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct UpdateResponse {
+    pub application: String,
+    pub message: String,
+    #[serde(default)]
+    pub complete: bool,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct AnnouncementEntry {
+    pub entry_id: String,
+    pub url: String,
+    pub title: String,
+    pub description: String,
+    pub priority: String,
+    #[serde(default)]
+    pub dismissed: bool,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct DatabaseItem<T> {
+    pub value: T,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct AnnouncementListResponse {
+    #[serde(default)]
+    pub entries: Vec<AnnouncementEntry>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct ThrottledState {
+    #[serde(default)]
+    pub flags: Vec<String>,
+}
+
+/// Memory of the host in kB.
+#[derive(Clone, Debug, Deserialize)]
+pub struct SystemMemory {
+    pub total: u64,
+    pub available: u64,
+}
+
+/// Host statistics. Updates leave out what did not change.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct ProcStats {
+    pub cpu_temp: Option<f64>,
+    pub throttled_state: Option<ThrottledState>,
+    pub system_memory: Option<SystemMemory>,
+    pub websocket_connections: Option<u32>,
+}
+
+/// Disk usage in bytes.
+#[derive(Clone, Debug, Deserialize)]
+pub struct DiskUsage {
+    pub total: u64,
+    pub free: u64,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct DirectoryInformation {
+    pub disk_usage: DiskUsage,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct FileInformation {
+    pub path: String,
+    /// Unix timestamp of the last modification.
+    pub modified: f64,
+    pub size: u64,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct TimelapseEvent {
+    pub action: String,
+    pub status: Option<String>,
+    #[serde(rename = "filename")]
+    pub file_name: Option<String>,
 }
@@ -1,22 +1,61 @@
+use std::{collections::HashMap, time::Duration};
+
 use super::{api::*, client_builder::ClientBuilder, Config};
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use jsonrpsee::{
     core::{
-        client::{ClientT, Subscription, SubscriptionClientT},
+        client::{ClientT, Error as ClientError, Subscription, SubscriptionClientT},
         params::ObjectParams,
     },
     rpc_params,
     ws_client::WsClient,
 };
+use reqwest::multipart;
+use serde::{de::DeserializeOwned, Serialize};
 use serde_json::json;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 const NAME: &str = env!("CARGO_PKG_NAME");
 const URL: &str = env!("CARGO_PKG_HOMEPAGE");
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(30);
+const UPLOAD_TIMEOUT: Duration = Duration::from_secs(300);
+/// Namespace of the items this client stores in Moonraker's database.
+const DATABASE_NAMESPACE: &str = "rusty_moon";
+/// Error code of database items that do not exist.
+const NOT_FOUND: i32 = 404;
+
+/// The URL of a file in one of Moonraker's roots.
+fn files_url(mut base: reqwest::Url, root: &str, path: &str) -> Result<reqwest::Url> {
+    base.path_segments_mut()
+        .map_err(|_| anyhow!("moonraker url cannot have a path"))?
+        .pop_if_empty()
+        .extend(["server", "files", root])
+        .extend(path.split('/'));
+    Ok(base)
+}
+
+/// The printer objects and fields that are queried and subscribed to.
+fn printer_objects() -> serde_json::Value {
+    json!({
+            "display_status": ["progress", "message"],
+            "exclude_object": ["objects", "excluded_objects", "current_object"],
+            "extruder": ["temperature", "target"],
+            "heater_bed": ["temperature", "target"],
+            "idle_timeout": ["state", "printing_time"],
+            "print_stats": ["info", "filename", "total_duration", "print_duration", "filament_used", "state", "message"],
+            "toolhead": ["position", "axis_minimum", "axis_maximum"],
+            "gcode_macro TIMELAPSE_TAKE_FRAME": ["takingframe"],
+            "webhooks": ["state", "state_message"],
+    })
+}
 
 pub struct Client {
     pub(crate) client: WsClient,
+    pub(crate) http: reqwest::Client,
     pub host: String,
+    pub port: u16,
+    pub default_webcam: Option<String>,
+    pub public_url: Option<String>,
 }
 
 impl Client {
@@ -24,6 +63,20 @@ impl Client {
         ClientBuilder::new(config)
     }
 
+    /// Returns the HTTP url of a file in one of Moonraker's file roots.
+    pub fn file_url(&self, root: &str, path: &str) -> Result<reqwest::Url> {
+        let base = reqwest::Url::parse(&format!("http://{}:{}/", self.host, self.port))?;
+        files_url(base, root, path)
+    }
+
+    /// URL of a file for Discord users, under the public URL if one is configured.
+    pub fn public_file_url(&self, root: &str, path: &str) -> Result<reqwest::Url> {
+        match &self.public_url {
+            Some(public_url) => files_url(reqwest::Url::parse(public_url)?, root, path),
+            None => self.file_url(root, path),
+        }
+    }
+
     pub async fn identify(&self) -> Result<()> {
         let mut params = ObjectParams::new();
         params.insert("client_name", NAME)?;
@@ -46,21 +99,291 @@ impl Client {
 
     pub async fn get_printer_status(&self) -> Result<PrinterObjectStatusResponse> {
         let mut params = ObjectParams::new();
-        params.insert(
-            "objects",
-            json!({
-                    "display_status": ["progress", "message"],
-                    "exclude_object": ["objects", "excluded_objects", "current_object"],
-                    "idle_timeout": ["state", "printing_time"],
-                    "print_stats": ["info", "filename", "total_duration", "print_duration", "filament_used", "state", "message"],
-                    "webhooks": ["state", "state_message"],
-            }),
-        )?;
+        params.insert("objects", printer_objects())?;
         let response = self.client.request("printer.objects.query", params).await?;
 
         Ok(response)
     }
 
+    pub async fn get_file_metadata(&self, file_name: impl AsRef<str>) -> Result<FileMetadata> {
+        let mut params = ObjectParams::new();
+        params.insert("filename", file_name.as_ref())?;
+        let response = self.client.request("server.files.metadata", params).await?;
+
+        Ok(response)
+    }
+
+    pub async fn list_files(&self, root: &str) -> Result<Vec<FileInformation>> {
+        let mut params = ObjectParams::new();
+        params.insert("root", root)?;
+        let response = self.client.request("server.files.list", params).await?;
+
+        Ok(response)
+    }
+
+    /// Downloads a file from one of Moonraker's file roots.
+    pub async fn download_file(&self, root: &str, path: &str) -> Result<Vec<u8>> {
+        self.download_file_limited(root, path, u64::MAX)
+            .await?
+            .ok_or_else(|| anyhow!("{:?} is too large", path))
+    }
+
+    /// Downloads a file, or returns `None` if it is larger than `size_limit` bytes.
+    pub async fn download_file_limited(
+        &self,
+        root: &str,
+        path: &str,
+        size_limit: u64,
+    ) -> Result<Option<Vec<u8>>> {
+        let mut response = self
+            .http
+            .get(self.file_url(root, path)?)
+            .timeout(DOWNLOAD_TIMEOUT)
+            .send()
+            .await?
+            .error_for_status()?;
+        if response
+            .content_length()
+            .is_some_and(|length| length > size_limit)
+        {
+            return Ok(None);
+        }
+
+        let mut data = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            data.extend_from_slice(&chunk);
+            if data.len() as u64 > size_limit {
+                return Ok(None);
+            }
+        }
+        Ok(Some(data))
+    }
+
+    /// Uploads a G-code file, returning its path in the gcodes root.
+    pub async fn upload_gcode(&self, file_name: &str, data: Vec<u8>) -> Result<String> {
+        let url = reqwest::Url::parse(&format!(
+            "http://{}:{}/server/files/upload",
+            self.host, self.port
+        ))?;
+        let form = multipart::Form::new().text("root", "gcodes").part(
+            "file",
+            multipart::Part::bytes(data).file_name(file_name.to_string()),
+        );
+        let response: FileUploadResponse = self
+            .http
+            .post(url)
+            .multipart(form)
+            .timeout(UPLOAD_TIMEOUT)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(response.item.path)
+    }
+
+    pub async fn get_job_queue(&self) -> Result<JobQueueStatus> {
+        let response = self
+            .client
+            .request("server.job_queue.status", rpc_params![])
+            .await?;
+
+        Ok(response)
+    }
+
+    pub async fn enqueue_job(&self, file_name: impl AsRef<str>) -> Result<JobQueueStatus> {
+        let mut params = ObjectParams::new();
+        params.insert("filenames", [file_name.as_ref()])?;
+        let response = self
+            .client
+            .request("server.job_queue.post_job", params)
+            .await?;
+
+        Ok(response)
+    }
+
+    pub async fn remove_job(&self, job_id: impl AsRef<str>) -> Result<JobQueueStatus> {
+        let mut params = ObjectParams::new();
+        params.insert("job_ids", [job_id.as_ref()])?;
+        let response = self
+            .client
+            .request("server.job_queue.delete_job", params)
+            .await?;
+
+        Ok(response)
+    }
+
+    pub async fn start_job_queue(&self) -> Result<JobQueueStatus> {
+        let response = self
+            .client
+            .request("server.job_queue.start", rpc_params![])
+            .await?;
+
+        Ok(response)
+    }
+
+    pub async fn pause_job_queue(&self) -> Result<JobQueueStatus> {
+        let response = self
+            .client
+            .request("server.job_queue.pause", rpc_params![])
+            .await?;
+
+        Ok(response)
+    }
+
+    pub async fn list_power_devices(&self) -> Result<Vec<PowerDeviceInformation>> {
+        let response: PowerDeviceListResult = self
+            .client
+            .request("machine.device_power.devices", rpc_params![])
+            .await?;
+        Ok(response.devices)
+    }
+
+    /// Switches a power device `on`, `off` or `toggle`s it, returning its new status.
+    pub async fn set_power_device(
+        &self,
+        device: impl AsRef<str>,
+        action: impl AsRef<str>,
+    ) -> Result<String> {
+        let mut params = ObjectParams::new();
+        let device = device.as_ref();
+        params.insert("device", device)?;
+        params.insert("action", action.as_ref())?;
+        let mut response: HashMap<String, String> = self
+            .client
+            .request("machine.device_power.post_device", params)
+            .await?;
+        response
+            .remove(device)
+            .ok_or_else(|| anyhow!("missing status of power device {:?}", device))
+    }
+
+    pub async fn get_update_status(&self) -> Result<UpdateStatusResponse> {
+        let response = self
+            .client
+            .request("machine.update.status", rpc_params![])
+            .await?;
+
+        Ok(response)
+    }
+
+    /// Updates a component managed by Moonraker's update manager. Updates can
+    /// outlast the request timeout, their progress and completion are reported
+    /// through `notify_update_response`.
+    pub async fn upgrade(&self, name: &str) -> Result<()> {
+        let mut params = ObjectParams::new();
+        let method = match name {
+            "klipper" | "moonraker" | "system" => format!("machine.update.{}", name),
+            _ => {
+                params.insert("name", name)?;
+                "machine.update.client".to_string()
+            }
+        };
+        let response: Result<String, ClientError> = self.client.request(&method, params).await;
+        match response {
+            Ok(response) => tracing::debug!("upgrade({:?}): {:?}", name, response),
+            Err(ClientError::RequestTimeout) => {
+                tracing::debug!("upgrade({:?}) is still running", name)
+            }
+            Err(err) => return Err(err.into()),
+        }
+
+        Ok(())
+    }
+
+    pub async fn list_announcements(&self) -> Result<Vec<AnnouncementEntry>> {
+        let mut params = ObjectParams::new();
+        params.insert("include_dismissed", false)?;
+        let response: AnnouncementListResponse = self
+            .client
+            .request("server.announcements.list", params)
+            .await?;
+        Ok(response.entries)
+    }
+
+    pub async fn dismiss_announcement(&self, entry_id: impl AsRef<str>) -> Result<()> {
+        let mut params = ObjectParams::new();
+        let entry_id = entry_id.as_ref();
+        params.insert("entry_id", entry_id)?;
+        let response: serde_json::Value = self
+            .client
+            .request("server.announcements.dismiss", params)
+            .await?;
+        tracing::debug!("dismiss_announcement({:?}): {:?}", entry_id, response);
+
+        Ok(())
+    }
+
+    /// Reads an item from Moonraker's database, or `None` if it was never stored.
+    pub async fn get_database_item<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
+        let mut params = ObjectParams::new();
+        params.insert("namespace", DATABASE_NAMESPACE)?;
+        params.insert("key", key)?;
+        let response: Result<DatabaseItem<T>, ClientError> = self
+            .client
+            .request("server.database.get_item", params)
+            .await;
+        match response {
+            Ok(item) => Ok(Some(item.value)),
+            Err(ClientError::Call(err)) if err.code() == NOT_FOUND => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    pub async fn post_database_item(&self, key: &str, value: impl Serialize) -> Result<()> {
+        let mut params = ObjectParams::new();
+        params.insert("namespace", DATABASE_NAMESPACE)?;
+        params.insert("key", key)?;
+        params.insert("value", value)?;
+        let _: serde_json::Value = self
+            .client
+            .request("server.database.post_item", params)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_proc_stats(&self) -> Result<ProcStats> {
+        let response = self
+            .client
+            .request("machine.proc_stats", rpc_params![])
+            .await?;
+
+        Ok(response)
+    }
+
+    /// Usage of the disk holding one of Moonraker's file roots.
+    pub async fn get_disk_usage(&self, root: &str) -> Result<DiskUsage> {
+        let mut params = ObjectParams::new();
+        params.insert("path", root)?;
+        params.insert("extended", false)?;
+        let response: DirectoryInformation = self
+            .client
+            .request("server.files.get_directory", params)
+            .await?;
+        Ok(response.disk_usage)
+    }
+
+    pub async fn start_print(&self, file_name: impl AsRef<str>) -> Result<()> {
+        let mut params = ObjectParams::new();
+        let file_name = file_name.as_ref();
+        params.insert("filename", file_name)?;
+        let response: String = self.client.request("printer.print.start", params).await?;
+        tracing::debug!("start_print({:?}): {:?}", file_name, response);
+
+        Ok(())
+    }
+
+    pub async fn run_gcode(&self, script: impl AsRef<str>) -> Result<()> {
+        let mut params = ObjectParams::new();
+        let script = script.as_ref();
+        params.insert("script", script)?;
+        let response: String = self.client.request("printer.gcode.script", params).await?;
+        tracing::debug!("run_gcode({:?}): {:?}", script, response);
+
+        Ok(())
+    }
+
     pub async fn register_remote_method(&self, method: impl AsRef<str>) -> Result<()> {
         let mut params = ObjectParams::new();
         let method = method.as_ref();
@@ -76,15 +399,7 @@ impl Client {
 
     pub async fn register_printer_subscription(&self) -> Result<PrinterObjectStatusResponse> {
         let mut params = ObjectParams::new();
-        params.insert(
-            "objects",
-            json!({
-                    "display_status": ["progress", "message"],
-                    "idle_timeout": ["state", "printing_time"],
-                    "print_stats": ["info", "filename", "total_duration", "print_duration", "filament_used", "state", "message"],
-                    "webhooks": ["state", "state_message"],
-            }),
-        )?;
+        params.insert("objects", printer_objects())?;
         let response: PrinterObjectStatusResponse = self
             .client
             .request("printer.objects.subscribe", params)
@@ -92,6 +407,14 @@ impl Client {
         Ok(response)
     }
 
+    pub async fn list_webcams(&self) -> Result<Vec<WebCamInformation>> {
+        let response: WebCamListResult = self
+            .client
+            .request("server.webcams.list", rpc_params![])
+            .await?;
+        Ok(response.webcams)
+    }
+
     pub async fn get_webcam_information(&self, name: impl AsRef<str>) -> Result<WebCamInformation> {
         let mut params = ObjectParams::new();
         params.insert("name", name.as_ref())?;
@@ -100,12 +423,18 @@ impl Client {
             .request("server.webcams.get_item", params)
             .await?;
         let response = serde_json::from_value::<WebCamInformationResult>(response)?;
-        let snapshot_url = if response.webcam.snapshot_url.starts_with("/") {
-            format!("http://{}{}", self.host, response.webcam.snapshot_url)
-        } else {
-            response.webcam.snapshot_url
+        let absolute_url = |url: String| {
+            if url.starts_with("/") {
+                format!("http://{}{}", self.host, url)
+            } else {
+                url
+            }
         };
-        Ok(WebCamInformation { snapshot_url })
+        Ok(WebCamInformation {
+            snapshot_url: absolute_url(response.webcam.snapshot_url),
+            stream_url: absolute_url(response.webcam.stream_url),
+            ..response.webcam
+        })
     }
 
     pub async fn subscribe_remote_method<Params>(
@@ -131,6 +460,84 @@ impl Client {
         Ok(sub)
     }
 
+    pub async fn subscribe_timelapse_event(&self) -> Result<Subscription<(TimelapseEvent,)>> {
+        let sub: Subscription<(TimelapseEvent,)> = self
+            .client
+            .subscribe_to_method("notify_timelapse_event")
+            .await?;
+        Ok(sub)
+    }
+
+    pub async fn subscribe_gcode_response(&self) -> Result<Subscription<(String,)>> {
+        let sub: Subscription<(String,)> = self
+            .client
+            .subscribe_to_method("notify_gcode_response")
+            .await?;
+        Ok(sub)
+    }
+
+    /// Notifies of any change to the job queue, the queue itself must be queried.
+    pub async fn subscribe_job_queue_changed(&self) -> Result<Subscription<serde_json::Value>> {
+        let sub: Subscription<serde_json::Value> = self
+            .client
+            .subscribe_to_method("notify_job_queue_changed")
+            .await?;
+        Ok(sub)
+    }
+
+    pub async fn subscribe_power_changed(&self) -> Result<Subscription<(PowerDeviceInformation,)>> {
+        let sub: Subscription<(PowerDeviceInformation,)> = self
+            .client
+            .subscribe_to_method("notify_power_changed")
+            .await?;
+        Ok(sub)
+    }
+
+    pub async fn subscribe_update_refreshed(
+        &self,
+    ) -> Result<Subscription<(UpdateStatusResponse,)>> {
+        let sub: Subscription<(UpdateStatusResponse,)> = self
+            .client
+            .subscribe_to_method("notify_update_refreshed")
+            .await?;
+        Ok(sub)
+    }
+
+    /// Only one subscriber at a time is supported.
+    pub async fn subscribe_update_response(&self) -> Result<Subscription<(UpdateResponse,)>> {
+        let sub: Subscription<(UpdateResponse,)> = self
+            .client
+            .subscribe_to_method("notify_update_response")
+            .await?;
+        Ok(sub)
+    }
+
+    pub async fn subscribe_announcement_update(
+        &self,
+    ) -> Result<Subscription<(AnnouncementListResponse,)>> {
+        let sub: Subscription<(AnnouncementListResponse,)> = self
+            .client
+            .subscribe_to_method("notify_announcement_update")
+            .await?;
+        Ok(sub)
+    }
+
+    pub async fn subscribe_proc_stat_update(&self) -> Result<Subscription<(ProcStats,)>> {
+        let sub: Subscription<(ProcStats,)> = self
+            .client
+            .subscribe_to_method("notify_proc_stat_update")
+            .await?;
+        Ok(sub)
+    }
+
+    pub async fn subscribe_cpu_throttled(&self) -> Result<Subscription<(ThrottledState,)>> {
+        let sub: Subscription<(ThrottledState,)> = self
+            .client
+            .subscribe_to_method("notify_cpu_throttled")
+            .await?;
+        Ok(sub)
+    }
+
     pub async fn subscribe_klippy_ready(&self) -> Result<Subscription<()>> {
         let sub: Subscription<()> = self
             .client
@@ -155,3 +562,18 @@ impl Client {
         Ok(sub)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn files_url_appends_to_base_path() {
+        let base = reqwest::Url::parse("https://example.com/printer/").unwrap();
+        let url = files_url(base, "timelapse", "sub dir/video.mp4").unwrap();
+        assert_eq!(
+            url.as_str(),
+            "https://example.com/printer/server/files/timelapse/sub%20dir/video.mp4"
+        );
+    }
+}
@@ -11,6 +11,8 @@ use super::{client::Client, Config};
 pub struct ClientBuilder {
     host: String,
     port: Option<u16>,
+    default_webcam: Option<String>,
+    public_url: Option<String>,
 }
 
 impl ClientBuilder {
@@ -18,6 +20,8 @@ impl ClientBuilder {
         ClientBuilder {
             host: config.host,
             port: config.port,
+            default_webcam: config.default_webcam,
+            public_url: config.public_url,
         }
     }
 }
@@ -28,17 +32,18 @@ impl IntoFuture for ClientBuilder {
 
     fn into_future(self) -> Self::IntoFuture {
         Box::pin(async move {
+            let port = self.port.unwrap_or(7125);
             let client = WsClientBuilder::default()
-                .build(format!(
-                    "ws://{}:{}/websocket",
-                    self.host,
-                    self.port.unwrap_or(7125)
-                ))
+                .build(format!("ws://{}:{}/websocket", self.host, port))
                 .await?;
 
             Ok(Client {
                 client,
+                http: reqwest::Client::new(),
                 host: self.host,
+                port,
+                default_webcam: self.default_webcam,
+                public_url: self.public_url,
             })
         })
     }
@@ -0,0 +1,181 @@
+use std::{collections::VecDeque, time::Duration};
+
+use anyhow::Result;
+use regex::Regex;
+use tokio::time::Instant;
+
+use super::ConsoleError;
+
+/// Console lines kept before an error.
+const CONTEXT_BEFORE: usize = 5;
+/// Console lines collected after an error.
+const CONTEXT_AFTER: usize = 5;
+/// How long to wait for the lines following an error.
+const CONTEXT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Watches the console for lines reporting errors.
+pub struct ConsoleWatcher {
+    patterns: Vec<Regex>,
+    history: VecDeque<String>,
+    pending: Option<(ConsoleError, Instant)>,
+}
+
+impl ConsoleWatcher {
+    pub fn new(patterns: &[String]) -> Result<Self> {
+        Ok(Self {
+            patterns: patterns
+                .iter()
+                .map(|pattern| Regex::new(pattern))
+                .collect::<Result<_, _>>()?,
+            history: VecDeque::with_capacity(CONTEXT_BEFORE),
+            pending: None,
+        })
+    }
+
+    /// Records a console line, returning an error once its context is complete.
+    ///
+    /// Another error while one is pending reports the pending one early.
+    pub fn push(&mut self, line: String) -> Option<ConsoleError> {
+        let mut reported = None;
+        if self.is_error(&line) {
+            reported = self.take_pending();
+            let mut context = self.history.iter().cloned().collect::<Vec<_>>();
+            let error_index = context.len();
+            context.push(line.clone());
+            self.pending = Some((
+                ConsoleError {
+                    line: line.clone(),
+                    context,
+                    error_index,
+                },
+                Instant::now() + CONTEXT_TIMEOUT,
+            ));
+        } else if let Some((error, _)) = &mut self.pending {
+            error.context.push(line.clone());
+            if error.context.len() >= error.error_index + 1 + CONTEXT_AFTER {
+                reported = self.take_pending();
+            }
+        }
+
+        if self.history.len() == CONTEXT_BEFORE {
+            self.history.pop_front();
+        }
+        self.history.push_back(line);
+        reported
+    }
+
+    /// When the pending error should be reported, even without all of its context.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.pending.as_ref().map(|(_, deadline)| *deadline)
+    }
+
+    pub fn take_pending(&mut self) -> Option<ConsoleError> {
+        self.pending.take().map(|(error, _)| error)
+    }
+
+    fn is_error(&self, line: &str) -> bool {
+        line.starts_with("!!") || self.patterns.iter().any(|pattern| pattern.is_match(line))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn push_all(watcher: &mut ConsoleWatcher, lines: &[&str]) -> Vec<ConsoleError> {
+        lines
+            .iter()
+            .filter_map(|line| watcher.push(line.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn push_reports_error_with_context() {
+        let mut watcher = ConsoleWatcher::new(&[]).unwrap();
+        let lines = (0..7).map(|index| format!("before {}", index));
+        for line in lines {
+            assert!(watcher.push(line).is_none());
+        }
+        assert!(watcher.push("!! Move out of range".to_string()).is_none());
+        let errors = push_all(&mut watcher, &["a", "b", "c", "d", "e"]);
+
+        assert_eq!(errors.len(), 1);
+        let error = &errors[0];
+        assert_eq!(error.line, "!! Move out of range");
+        assert_eq!(error.error_index, CONTEXT_BEFORE);
+        assert_eq!(
+            error.context,
+            [
+                "before 2",
+                "before 3",
+                "before 4",
+                "before 5",
+                "before 6",
+                "!! Move out of range",
+                "a",
+                "b",
+                "c",
+                "d",
+                "e"
+            ]
+        );
+        assert!(watcher.deadline().is_none());
+    }
+
+    #[test]
+    fn push_matches_configured_patterns() {
+        let mut watcher =
+            ConsoleWatcher::new(&["not heating at expected rate".to_string()]).unwrap();
+        push_all(
+            &mut watcher,
+            &["ok", "Heater extruder not heating at expected rate"],
+        );
+        assert!(watcher.deadline().is_some());
+
+        let error = watcher.take_pending().unwrap();
+        assert_eq!(error.error_index, 1);
+        assert_eq!(
+            error.context,
+            ["ok", "Heater extruder not heating at expected rate"]
+        );
+    }
+
+    #[test]
+    fn push_reports_pending_error_on_next_error() {
+        let mut watcher = ConsoleWatcher::new(&[]).unwrap();
+        let errors = push_all(
+            &mut watcher,
+            &["ok", "!! First", "a", "b", "!! Second", "c"],
+        );
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line, "!! First");
+        assert_eq!(errors[0].context, ["ok", "!! First", "a", "b"]);
+
+        let error = watcher.take_pending().unwrap();
+        assert_eq!(error.line, "!! Second");
+        assert_eq!(error.error_index, 4);
+        assert_eq!(
+            error.context,
+            ["ok", "!! First", "a", "b", "!! Second", "c"]
+        );
+    }
+
+    #[test]
+    fn push_keeps_history_while_error_is_pending() {
+        let mut watcher = ConsoleWatcher::new(&[]).unwrap();
+        let lines = ["!! First", "a", "b", "c", "d", "e", "f", "g", "!! Second"];
+        let errors = push_all(&mut watcher, &lines);
+        assert_eq!(errors.len(), 1);
+
+        let error = watcher.take_pending().unwrap();
+        assert_eq!(error.context, ["c", "d", "e", "f", "g", "!! Second"]);
+    }
+
+    #[test]
+    fn push_ignores_ordinary_lines() {
+        let mut watcher = ConsoleWatcher::new(&[]).unwrap();
+        assert!(push_all(&mut watcher, &["ok", "// echo: done", "B:60.0 /60.0"]).is_empty());
+        assert!(watcher.deadline().is_none());
+    }
+}
@@ -0,0 +1,182 @@
+use std::time::{Duration, Instant};
+
+use super::HostStatus;
+
+/// How far the CPU temperature has to drop below the limit to clear the alert.
+const CPU_TEMPERATURE_HYSTERESIS: f64 = 5.0;
+/// Least time between two alerts of the same kind.
+const ALERT_COOLDOWN: Duration = Duration::from_secs(30 * 60);
+
+/// Thresholds for host health alerts.
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct HostConfig {
+    /// CPU temperature in °C.
+    #[serde(default = "default_max_cpu_temperature")]
+    pub max_cpu_temperature: f64,
+    /// Free space in MiB on the disk holding the gcodes folder.
+    #[serde(default = "default_min_free_disk")]
+    pub min_free_disk: u64,
+}
+
+fn default_max_cpu_temperature() -> f64 {
+    80.0
+}
+
+fn default_min_free_disk() -> u64 {
+    500
+}
+
+impl Default for HostConfig {
+    fn default() -> Self {
+        Self {
+            max_cpu_temperature: default_max_cpu_temperature(),
+            min_free_disk: default_min_free_disk(),
+        }
+    }
+}
+
+/// Raises alerts when the host becomes unhealthy, once until it recovers.
+pub struct HostMonitor {
+    config: HostConfig,
+    throttled: Alert,
+    low_disk: Alert,
+    hot: Alert,
+}
+
+impl HostMonitor {
+    pub fn new(config: HostConfig) -> Self {
+        Self {
+            config,
+            throttled: Alert::default(),
+            low_disk: Alert::default(),
+            hot: Alert::default(),
+        }
+    }
+
+    pub fn check(&mut self, host: &HostStatus) -> Vec<String> {
+        let mut alerts = Vec::new();
+
+        let throttling = host.current_throttling().collect::<Vec<_>>();
+        if self.throttled.update(!throttling.is_empty()) {
+            alerts.push(format!("Host is throttled: {}", throttling.join(", ")));
+        }
+
+        let free_disk = host.disk_free.map(|free| free / 1024 / 1024);
+        let low_disk = free_disk.is_some_and(|free| free < self.config.min_free_disk);
+        if self.low_disk.update(low_disk) {
+            alerts.push(format!(
+                "Low disk space for G-code files: {} MiB free",
+                free_disk.unwrap_or_default()
+            ));
+        }
+
+        let max_temperature = match self.hot.active {
+            true => self.config.max_cpu_temperature - CPU_TEMPERATURE_HYSTERESIS,
+            false => self.config.max_cpu_temperature,
+        };
+        let hot = host
+            .cpu_temperature
+            .is_some_and(|temperature| temperature > max_temperature);
+        if self.hot.update(hot) {
+            alerts.push(format!(
+                "Host CPU temperature is {:.1}°C",
+                host.cpu_temperature.unwrap_or_default()
+            ));
+        }
+
+        alerts
+    }
+}
+
+/// A condition that is alerted when it starts, at most once per [`ALERT_COOLDOWN`].
+#[derive(Default)]
+struct Alert {
+    active: bool,
+    raised_at: Option<Instant>,
+}
+
+impl Alert {
+    /// Records the condition, returning whether to raise the alert.
+    fn update(&mut self, condition: bool) -> bool {
+        let started = condition && !self.active;
+        self.active = condition;
+        if !started
+            || self
+                .raised_at
+                .is_some_and(|raised_at| raised_at.elapsed() < ALERT_COOLDOWN)
+        {
+            return false;
+        }
+        self.raised_at = Some(Instant::now());
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor() -> HostMonitor {
+        HostMonitor::new(HostConfig {
+            max_cpu_temperature: 80.0,
+            min_free_disk: 500,
+        })
+    }
+
+    fn at_temperature(temperature: f64) -> HostStatus {
+        HostStatus {
+            cpu_temperature: Some(temperature),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn check_alerts_once_until_recovered() {
+        let mut monitor = monitor();
+        assert!(monitor.check(&at_temperature(70.0)).is_empty());
+        assert_eq!(monitor.check(&at_temperature(81.0)).len(), 1);
+        assert!(monitor.check(&at_temperature(82.0)).is_empty());
+    }
+
+    #[test]
+    fn check_clears_cpu_temperature_below_hysteresis() {
+        let mut monitor = monitor();
+        monitor.check(&at_temperature(81.0));
+        monitor.check(&at_temperature(79.0));
+        assert!(monitor.hot.active);
+        monitor.check(&at_temperature(74.0));
+        assert!(!monitor.hot.active);
+    }
+
+    #[test]
+    fn check_rate_limits_flapping_conditions() {
+        let mut monitor = monitor();
+        let throttled = HostStatus {
+            throttled: vec!["Frequency Capped".to_string()],
+            ..Default::default()
+        };
+        let recovered = HostStatus {
+            throttled: vec!["Previously Frequency Capped".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            monitor.check(&throttled),
+            ["Host is throttled: Frequency Capped"]
+        );
+        assert!(monitor.check(&recovered).is_empty());
+        assert!(monitor.check(&throttled).is_empty());
+    }
+
+    #[test]
+    fn check_alerts_low_disk() {
+        let mut monitor = monitor();
+        let host = HostStatus {
+            disk_free: Some(100 * 1024 * 1024),
+            ..Default::default()
+        };
+        assert_eq!(
+            monitor.check(&host),
+            ["Low disk space for G-code files: 100 MiB free"]
+        );
+    }
+}
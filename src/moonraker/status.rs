@@ -1,11 +1,31 @@
-use std::fmt::{self, Display, Formatter};
+use std::{
+    fmt::{self, Display, Formatter},
+    time::Duration,
+};
 
-use super::api::{ExcludeObject, PrintStats, PrinterObjectStatus};
+use tokio::time::Instant;
+
+use super::api::{
+    DiskUsage, ExcludeObject, FileMetadata, Heater, JobQueueStatus, PowerDeviceInformation,
+    PrintStats, PrinterObjectStatus, ProcStats, Toolhead,
+};
+
+/// How long a higher layer estimate has to hold before it is taken.
+const LAYER_HOLD: Duration = Duration::from_secs(2);
 
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct ObjectInformation {
     pub name: String,
     pub excluded: bool,
+    pub polygon: Vec<[f64; 2]>,
+    pub center: Option<[f64; 2]>,
+}
+
+/// The XY area the toolhead can reach.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct BedArea {
+    pub min: [f64; 2],
+    pub max: [f64; 2],
 }
 
 #[derive(Clone, Debug, Default, PartialEq)]
@@ -13,12 +33,24 @@ pub struct JobInfo {
     pub file_name: String,
     pub current_layer: u16,
     pub total_layer: u16,
+    pub layers_estimated: bool,
     pub objects: Vec<ObjectInformation>,
+    pub current_object: Option<String>,
+    pub bed: Option<BedArea>,
+}
+
+/// Temperatures of a heater in °C.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Temperature {
+    pub current: f64,
+    pub target: f64,
 }
 
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct Printer {
     pub job: Option<JobInfo>,
+    pub extruder: Option<Temperature>,
+    pub heater_bed: Option<Temperature>,
 }
 
 #[derive(Clone, Debug, Default, PartialEq)]
@@ -34,10 +66,51 @@ pub enum State {
     Error(String),
 }
 
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct QueuedJob {
+    pub id: String,
+    pub file_name: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct JobQueue {
+    /// One of `ready`, `loading`, `starting` or `paused`.
+    pub state: String,
+    pub jobs: Vec<QueuedJob>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PowerDevice {
+    pub name: String,
+    /// One of `on`, `off`, `init` or `error`.
+    pub status: String,
+    pub locked_while_printing: bool,
+}
+
+/// Health of the machine running Moonraker.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HostStatus {
+    /// In °C.
+    pub cpu_temperature: Option<f64>,
+    /// Throttling conditions reported by the Raspberry Pi firmware.
+    pub throttled: Vec<String>,
+    /// In kB.
+    pub memory_total: Option<u64>,
+    /// In kB.
+    pub memory_available: Option<u64>,
+    pub websocket_connections: u32,
+    /// Free bytes on the disk holding the gcodes folder.
+    pub disk_free: Option<u64>,
+    pub disk_total: Option<u64>,
+}
+
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct Status {
     pub printer: Option<Printer>,
     pub state: State,
+    pub queue: JobQueue,
+    pub power_devices: Vec<PowerDevice>,
+    pub host: HostStatus,
 }
 
 impl Display for State {
@@ -61,7 +134,6 @@ impl From<&PrintStats> for State {
             None => Self::default(),
             Some("standby") => Self::Standby,
             Some("printing") => Self::Printing,
-            // TODO: Handle timelapse pauses
             Some("paused") => Self::Paused,
             Some("complete") => Self::Complete,
             Some("error") => Self::Error(value.message.clone().unwrap_or_default()),
@@ -70,22 +142,139 @@ impl From<&PrintStats> for State {
     }
 }
 
-impl From<PrinterObjectStatus> for Printer {
-    fn from(value: PrinterObjectStatus) -> Self {
+impl From<&PrinterObjectStatus> for State {
+    fn from(value: &PrinterObjectStatus) -> Self {
+        match Self::from(&value.print_stats) {
+            // moonraker-timelapse parks the toolhead while taking a frame
+            Self::Paused if value.timelapse_take_frame.taking_frame => Self::Printing,
+            state => state,
+        }
+    }
+}
+
+impl From<(&PrinterObjectStatus, Option<&FileMetadata>)> for Printer {
+    fn from(tuple: (&PrinterObjectStatus, Option<&FileMetadata>)) -> Self {
+        let (value, metadata) = tuple;
         Self {
-            job: match State::from(&value.print_stats) {
-                State::Printing | State::Paused | State::Complete => Some(JobInfo {
-                    current_layer: value.print_stats.info.current_layer.unwrap_or_default(),
-                    total_layer: value.print_stats.info.total_layer.unwrap_or_default(),
-                    file_name: value
-                        .print_stats
-                        .file_name
-                        .clone()
-                        .unwrap_or("unknown".to_string()),
-                    objects: (&value.exclude_object).into(),
-                }),
+            job: match State::from(value) {
+                State::Printing | State::Paused | State::Complete => {
+                    let info = value.print_stats.info;
+                    let estimate = metadata
+                        .and_then(|metadata| estimate_layers(&value.toolhead.position, metadata));
+                    let (current_layer, total_layer, layers_estimated) =
+                        match (info.current_layer, info.total_layer, estimate) {
+                            (Some(current), Some(total), _) => (current, total, false),
+                            (current, total, Some((estimated_current, estimated_total))) => (
+                                current.unwrap_or(estimated_current),
+                                total.unwrap_or(estimated_total),
+                                true,
+                            ),
+                            (current, total, None) => (
+                                current.unwrap_or_default(),
+                                total.unwrap_or_default(),
+                                false,
+                            ),
+                        };
+                    Some(JobInfo {
+                        current_layer,
+                        total_layer,
+                        layers_estimated,
+                        file_name: value
+                            .print_stats
+                            .file_name
+                            .clone()
+                            .unwrap_or("unknown".to_string()),
+                        objects: (&value.exclude_object).into(),
+                        current_object: value.exclude_object.current_object.clone(),
+                        bed: BedArea::from_toolhead(&value.toolhead),
+                    })
+                }
                 _ => None,
             },
+            extruder: value.extruder.as_ref().and_then(Temperature::from_heater),
+            heater_bed: value.heater_bed.as_ref().and_then(Temperature::from_heater),
+        }
+    }
+}
+
+impl Temperature {
+    fn from_heater(heater: &Heater) -> Option<Self> {
+        Some(Self {
+            current: heater.temperature?,
+            target: heater.target.unwrap_or_default(),
+        })
+    }
+}
+
+/// Estimates the current and total layer from the toolhead Z position for
+/// slicers that do not emit `SET_PRINT_STATS_INFO`.
+fn estimate_layers(position: &[f64], metadata: &FileMetadata) -> Option<(u16, u16)> {
+    let z = *position.get(2)?;
+    let layer_height = metadata.layer_height.filter(|height| *height > 0.0)?;
+    let first_layer_height = metadata.first_layer_height.unwrap_or(layer_height);
+    let layer_at = |height: f64| {
+        if height <= first_layer_height {
+            1
+        } else {
+            (((height - first_layer_height) / layer_height).round() as u16).saturating_add(1)
+        }
+    };
+    let total_layer = metadata
+        .layer_count
+        .or_else(|| metadata.object_height.map(layer_at))?;
+
+    Some((layer_at(z).min(total_layer), total_layer))
+}
+
+/// Smooths the layer estimated from the toolhead Z, which jumps up during
+/// Z-hops and parking moves.
+#[derive(Debug, Default)]
+pub(super) struct LayerTracker {
+    layer: u16,
+    /// The lowest estimate since the estimates went above the layer, and when they did.
+    rising: Option<(u16, Instant)>,
+}
+
+impl LayerTracker {
+    /// Returns the current layer, raising it to a higher estimate only after
+    /// the estimates stayed above it for [`LAYER_HOLD`] while printing.
+    pub(super) fn update(&mut self, estimate: u16, printing: bool) -> u16 {
+        if !printing {
+            self.rising = None;
+            // Nothing tracked yet, e.g. after connecting to a paused job
+            if self.layer == 0 {
+                return estimate;
+            }
+            return self.layer;
+        }
+        if estimate <= self.layer {
+            self.rising = None;
+            return self.layer;
+        }
+
+        let (lowest, since) = self.rising.get_or_insert((estimate, Instant::now()));
+        *lowest = (*lowest).min(estimate);
+        if since.elapsed() >= LAYER_HOLD {
+            self.layer = *lowest;
+            self.rising = None;
+        }
+        self.layer
+    }
+}
+
+impl BedArea {
+    fn from_toolhead(toolhead: &Toolhead) -> Option<Self> {
+        match (
+            toolhead.axis_minimum.as_slice(),
+            toolhead.axis_maximum.as_slice(),
+        ) {
+            ([min_x, min_y, ..], [max_x, max_y, ..]) if max_x > min_x && max_y > min_y => {
+                Some(Self {
+                    min: [*min_x, *min_y],
+                    max: [*max_x, *max_y],
+                })
+            }
+            _ => None,
         }
     }
 }
@@ -98,16 +287,177 @@ impl From<&ExcludeObject> for Vec<ObjectInformation> {
             .map(|object| ObjectInformation {
                 name: object.name.clone(),
                 excluded: value.excluded_objects.contains(&object.name),
+                polygon: object.polygon.clone(),
+                center: object.center,
             })
             .collect()
     }
 }
 
-impl From<&PrinterObjectStatus> for Status {
-    fn from(value: &PrinterObjectStatus) -> Self {
+impl From<JobQueueStatus> for JobQueue {
+    fn from(value: JobQueueStatus) -> Self {
+        Self {
+            state: value.queue_state,
+            jobs: value
+                .queued_jobs
+                .into_iter()
+                .map(|job| QueuedJob {
+                    id: job.job_id,
+                    file_name: job.file_name,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl HostStatus {
+    /// Applies a statistics update, keeping what it leaves out.
+    pub(super) fn update(&mut self, stats: ProcStats) {
+        if let Some(cpu_temperature) = stats.cpu_temp {
+            self.cpu_temperature = Some(cpu_temperature);
+        }
+        if let Some(throttled_state) = stats.throttled_state {
+            self.throttled = throttled_state.flags;
+        }
+        if let Some(memory) = stats.system_memory {
+            self.memory_total = Some(memory.total);
+            self.memory_available = Some(memory.available);
+        }
+        if let Some(websocket_connections) = stats.websocket_connections {
+            self.websocket_connections = websocket_connections;
+        }
+    }
+
+    pub(super) fn update_disk(&mut self, disk_usage: DiskUsage) {
+        self.disk_free = Some(disk_usage.free);
+        self.disk_total = Some(disk_usage.total);
+    }
+
+    /// Throttling conditions that are present now, not just since boot.
+    pub fn current_throttling(&self) -> impl Iterator<Item = &str> {
+        self.throttled
+            .iter()
+            .map(String::as_str)
+            .filter(|flag| !flag.starts_with("Previously"))
+    }
+}
+
+impl From<PowerDeviceInformation> for PowerDevice {
+    fn from(value: PowerDeviceInformation) -> Self {
+        Self {
+            name: value.device,
+            status: value.status,
+            locked_while_printing: value.locked_while_printing,
+        }
+    }
+}
+
+impl From<(&PrinterObjectStatus, Option<&FileMetadata>)> for Status {
+    fn from(tuple: (&PrinterObjectStatus, Option<&FileMetadata>)) -> Self {
+        let (value, metadata) = tuple;
         Self {
-            printer: Some(Printer::from(value.clone())),
-            state: State::from(&value.print_stats),
+            printer: Some(Printer::from((value, metadata))),
+            state: State::from(value),
+            queue: JobQueue::default(),
+            power_devices: Vec::new(),
+            host: HostStatus::default(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use tokio::time;
+
+    use super::*;
+
+    fn file_metadata() -> FileMetadata {
+        FileMetadata {
+            layer_height: Some(0.2),
+            first_layer_height: Some(0.3),
+            object_height: Some(10.1),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn estimate_layers_from_z() {
+        let metadata = file_metadata();
+        assert_eq!(estimate_layers(&[0.0, 0.0, 0.3], &metadata), Some((1, 50)));
+        assert_eq!(estimate_layers(&[0.0, 0.0, 0.5], &metadata), Some((2, 50)));
+        assert_eq!(
+            estimate_layers(&[0.0, 0.0, 20.0], &metadata),
+            Some((50, 50))
+        );
+    }
+
+    #[test]
+    fn estimate_layers_prefers_layer_count() {
+        let metadata = FileMetadata {
+            layer_count: Some(42),
+            ..file_metadata()
+        };
+        assert_eq!(estimate_layers(&[0.0, 0.0, 0.5], &metadata), Some((2, 42)));
+    }
+
+    #[test]
+    fn estimate_layers_needs_layer_height() {
+        let metadata = FileMetadata {
+            layer_height: None,
+            ..file_metadata()
+        };
+        assert_eq!(estimate_layers(&[0.0, 0.0, 0.5], &metadata), None);
+        assert_eq!(estimate_layers(&[0.0, 0.0], &file_metadata()), None);
+    }
+
+    #[test]
+    fn estimate_layers_saturates() {
+        let metadata = FileMetadata {
+            layer_count: Some(u16::MAX),
+            ..file_metadata()
+        };
+        assert_eq!(
+            estimate_layers(&[0.0, 0.0, 1e9], &metadata),
+            Some((u16::MAX, u16::MAX))
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn layer_tracker_ignores_z_hops() {
+        let mut tracker = LayerTracker {
+            layer: 5,
+            rising: None,
+        };
+        assert_eq!(tracker.update(7, true), 5);
+        time::advance(Duration::from_millis(500)).await;
+        // Back down on the layer before the hold passed
+        assert_eq!(tracker.update(5, true), 5);
+        time::advance(LAYER_HOLD).await;
+        assert_eq!(tracker.update(7, true), 5);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn layer_tracker_raises_to_lowest_estimate_after_hold() {
+        let mut tracker = LayerTracker {
+            layer: 5,
+            rising: None,
+        };
+        assert_eq!(tracker.update(6, true), 5);
+        time::advance(Duration::from_millis(500)).await;
+        // A Z-hop on the new layer
+        assert_eq!(tracker.update(8, true), 5);
+        time::advance(LAYER_HOLD).await;
+        assert_eq!(tracker.update(8, true), 6);
+        assert_eq!(tracker.update(6, true), 6);
+    }
+
+    #[test]
+    fn layer_tracker_keeps_layer_while_not_printing() {
+        let mut tracker = LayerTracker {
+            layer: 4,
+            rising: None,
+        };
+        assert_eq!(tracker.update(9, false), 4);
+        assert_eq!(LayerTracker::default().update(9, false), 9);
+    }
+}
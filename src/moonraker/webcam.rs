@@ -1,18 +1,330 @@
-use anyhow::Result;
-use std::{fs::File, io::Write};
+use anyhow::{anyhow, bail, Result};
+use image::{codecs::jpeg::JpegEncoder, imageops::FilterType, ImageFormat, ImageReader};
+use reqwest::header::CONTENT_TYPE;
+use std::{io::Cursor, time::Duration};
+use tokio::time;
 
-use super::client::Client;
+use super::{api::WebCamInformation, client::Client};
 
-pub async fn get_webcam_snapshot(client: &Client, webcam: impl AsRef<str>) -> Result<Option<File>> {
-    let info = client.get_webcam_information(&webcam).await?;
+const JPEG_QUALITY: u8 = 90;
+const JPEG_CONTENT_TYPE: &str = "image/jpeg";
+const SNAPSHOT_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_SNAPSHOT_SIZE: usize = 10 * 1024 * 1024;
+const SNAPSHOT_ATTEMPTS: u32 = 3;
+const SNAPSHOT_RETRY_DELAY: Duration = Duration::from_millis(500);
+/// Upper bound for all attempts together, including the stream fallbacks.
+const SNAPSHOT_DEADLINE: Duration = Duration::from_secs(30);
+
+/// An encoded webcam image.
+#[derive(Clone, Debug)]
+pub struct Image {
+    pub data: Vec<u8>,
+    pub content_type: String,
+}
+
+impl Image {
+    fn jpeg(data: Vec<u8>) -> Self {
+        Self {
+            data,
+            content_type: JPEG_CONTENT_TYPE.to_string(),
+        }
+    }
+
+    /// Returns `name` with the file extension matching the content type.
+    pub fn file_name(&self, name: &str) -> String {
+        let extension = match self.content_type.as_str() {
+            "image/png" => "png",
+            "image/gif" => "gif",
+            "image/webp" => "webp",
+            _ => "jpeg",
+        };
+        format!("{}.{}", name, extension)
+    }
+}
+
+/// Picks the webcam to use when none is named: the configured default, or
+/// else the first webcam known to Moonraker.
+pub async fn resolve_webcam(client: &Client, webcam: Option<&str>) -> Result<Option<String>> {
+    if let Some(webcam) = webcam.or(client.default_webcam.as_deref()) {
+        return Ok(Some(webcam.to_string()));
+    }
+    let webcams = client.list_webcams().await?;
+    Ok(webcams.into_iter().next().map(|webcam| webcam.name))
+}
+
+/// Takes a snapshot with the webcam, retrying with a growing delay when it
+/// fails, until [`SNAPSHOT_DEADLINE`] passes.
+pub async fn get_webcam_snapshot(client: &Client, webcam: impl AsRef<str>) -> Result<Image> {
+    let webcam = webcam.as_ref();
+    time::timeout(SNAPSHOT_DEADLINE, retry_webcam_snapshot(client, webcam))
+        .await
+        .map_err(|_| anyhow!("snapshot with {:?} timed out", webcam))?
+}
+
+async fn retry_webcam_snapshot(client: &Client, webcam: &str) -> Result<Image> {
+    let mut attempt = 1;
+    loop {
+        match try_webcam_snapshot(client, webcam).await {
+            Ok(image) => return Ok(image),
+            Err(err) if attempt < SNAPSHOT_ATTEMPTS => {
+                tracing::warn!(
+                    "snapshot attempt {} with {:?} failed: {:?}",
+                    attempt,
+                    webcam,
+                    err
+                );
+                time::sleep(SNAPSHOT_RETRY_DELAY * attempt).await;
+                attempt += 1;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+async fn try_webcam_snapshot(client: &Client, webcam: &str) -> Result<Image> {
+    let info = client.get_webcam_information(webcam).await?;
     tracing::debug!("webcam snapshot url: {:?}", info.snapshot_url);
-    let response = reqwest::get(info.snapshot_url).await?;
-    let mut file = tempfile::Builder::new()
-        .prefix("rusty_moon_")
-        .suffix(".jpeg")
-        .tempfile()?;
-    let content = response.bytes().await?;
-    file.write_all(&content)?;
-    tracing::debug!("saved webcam snapshot to {:?}", file.path());
-    Ok(Some(file.reopen()?))
+    let image = match fetch_snapshot(client, &info.snapshot_url).await {
+        Ok(image) => image,
+        Err(err) if !info.stream_url.is_empty() => {
+            tracing::warn!(
+                "error fetching snapshot, grabbing a frame from {:?}: {:?}",
+                info.stream_url,
+                err
+            );
+            grab_stream_frame(client, &info.stream_url).await?
+        }
+        Err(err) => return Err(err),
+    };
+    validate(&image)?;
+    if image.content_type != JPEG_CONTENT_TYPE {
+        return Ok(image);
+    }
+
+    let image = tokio::task::spawn_blocking(move || {
+        transform(&image.data, &info)
+            .map(|transformed| transformed.map(Image::jpeg).unwrap_or(image))
+    })
+    .await??;
+    tracing::debug!("took webcam snapshot of {} bytes", image.data.len());
+    Ok(image)
+}
+
+async fn fetch_snapshot(client: &Client, url: &str) -> Result<Image> {
+    if url.is_empty() {
+        bail!("no snapshot url configured");
+    }
+    let mut response = client
+        .http
+        .get(url)
+        .timeout(SNAPSHOT_TIMEOUT)
+        .send()
+        .await?
+        .error_for_status()?;
+    let content_type = match response.headers().get(CONTENT_TYPE) {
+        Some(value) => value
+            .to_str()?
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_string(),
+        None => JPEG_CONTENT_TYPE.to_string(),
+    };
+    if response
+        .content_length()
+        .is_some_and(|length| length > MAX_SNAPSHOT_SIZE as u64)
+    {
+        bail!("snapshot exceeds {} bytes", MAX_SNAPSHOT_SIZE);
+    }
+
+    let mut data = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        data.extend_from_slice(&chunk);
+        if data.len() > MAX_SNAPSHOT_SIZE {
+            bail!("snapshot exceeds {} bytes", MAX_SNAPSHOT_SIZE);
+        }
+    }
+    Ok(Image { data, content_type })
+}
+
+fn validate(image: &Image) -> Result<()> {
+    if !image.content_type.starts_with("image/") {
+        bail!("unexpected snapshot content type: {:?}", image.content_type);
+    }
+    if image.data.is_empty() {
+        bail!("empty snapshot");
+    }
+    if image.content_type == JPEG_CONTENT_TYPE && !image.data.starts_with(&[0xFF, 0xD8]) {
+        bail!("snapshot is not a JPEG image");
+    }
+    Ok(())
+}
+
+/// Reads a multipart MJPEG stream until the first complete JPEG frame.
+async fn grab_stream_frame(client: &Client, url: &str) -> Result<Image> {
+    time::timeout(SNAPSHOT_TIMEOUT, async {
+        let mut response = client.http.get(url).send().await?.error_for_status()?;
+        let mut buffer = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            buffer.extend_from_slice(&chunk);
+            if let Some(frame) = find_jpeg_frame(&buffer) {
+                return Ok(Image::jpeg(frame.to_vec()));
+            }
+            if buffer.len() > MAX_SNAPSHOT_SIZE {
+                bail!("no complete frame in the first {} bytes", buffer.len());
+            }
+        }
+        bail!("stream ended before a complete frame")
+    })
+    .await?
+}
+
+/// Finds the bytes from the first JPEG start of image marker to the
+/// following end of image marker.
+fn find_jpeg_frame(buffer: &[u8]) -> Option<&[u8]> {
+    let start = buffer
+        .windows(2)
+        .position(|marker| marker == [0xFF, 0xD8])?;
+    let length = buffer[start..]
+        .windows(2)
+        .skip(2)
+        .position(|marker| marker == [0xFF, 0xD9])?
+        + 4;
+    Some(&buffer[start..start + length])
+}
+
+/// Applies the flips, rotation and aspect ratio of the webcam configuration,
+/// the same way Mainsail displays the stream. Returns `None` when the image
+/// is already displayed as is.
+fn transform(content: &[u8], info: &WebCamInformation) -> Result<Option<Vec<u8>>> {
+    let rotation = info.rotation % 360;
+    let (width, height) =
+        ImageReader::with_format(Cursor::new(content), ImageFormat::Jpeg).into_dimensions()?;
+    let (width, height) = match rotation {
+        90 | 270 => (height, width),
+        _ => (width, height),
+    };
+    let target_height = info
+        .aspect_ratio
+        .as_deref()
+        .and_then(parse_aspect_ratio)
+        .map(|ratio| (f64::from(width) / ratio).round() as u32)
+        .filter(|target_height| *target_height > 0 && target_height.abs_diff(height) > 1);
+    if !info.flip_horizontal && !info.flip_vertical && rotation == 0 && target_height.is_none() {
+        return Ok(None);
+    }
+
+    let mut image = image::load_from_memory_with_format(content, ImageFormat::Jpeg)?;
+    if info.flip_horizontal {
+        image = image.fliph();
+    }
+    if info.flip_vertical {
+        image = image.flipv();
+    }
+    image = match rotation {
+        90 => image.rotate90(),
+        180 => image.rotate180(),
+        270 => image.rotate270(),
+        _ => image,
+    };
+    if let Some(target_height) = target_height {
+        image = image.resize_exact(width, target_height, FilterType::Triangle);
+    }
+
+    let mut output = Vec::new();
+    image.write_with_encoder(JpegEncoder::new_with_quality(&mut output, JPEG_QUALITY))?;
+    Ok(Some(output))
+}
+
+/// Parses an aspect ratio such as `16:9` into width divided by height.
+fn parse_aspect_ratio(aspect_ratio: &str) -> Option<f64> {
+    let (width, height) = aspect_ratio.split_once(':')?;
+    let width = width.trim().parse::<f64>().ok()?;
+    let height = height.trim().parse::<f64>().ok()?;
+    (width > 0.0 && height > 0.0).then_some(width / height)
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{Rgb, RgbImage};
+
+    use super::*;
+
+    /// A wide white JPEG with a red square in its top left corner.
+    fn marked_jpeg() -> Vec<u8> {
+        let image = RgbImage::from_fn(32, 16, |x, y| {
+            if x < 8 && y < 8 {
+                Rgb([255, 0, 0])
+            } else {
+                Rgb([255, 255, 255])
+            }
+        });
+        let mut output = Vec::new();
+        image
+            .write_with_encoder(JpegEncoder::new_with_quality(&mut output, JPEG_QUALITY))
+            .unwrap();
+        output
+    }
+
+    fn is_red(pixel: &Rgb<u8>) -> bool {
+        let [red, green, blue] = pixel.0;
+        red > 200 && green < 60 && blue < 60
+    }
+
+    #[test]
+    fn transform_flips_before_rotating() {
+        let info = WebCamInformation {
+            flip_horizontal: true,
+            rotation: 90,
+            ..Default::default()
+        };
+        let output = transform(&marked_jpeg(), &info).unwrap().unwrap();
+        let image = image::load_from_memory_with_format(&output, ImageFormat::Jpeg)
+            .unwrap()
+            .to_rgb8();
+        assert_eq!(image.dimensions(), (16, 32));
+        // Flipping moves the square to the top right, rotating it clockwise to the bottom right
+        assert!(is_red(image.get_pixel(12, 28)));
+        assert!(!is_red(image.get_pixel(3, 3)));
+        assert!(!is_red(image.get_pixel(12, 3)));
+        assert!(!is_red(image.get_pixel(3, 28)));
+    }
+
+    #[test]
+    fn transform_skips_untouched_images() {
+        let info = WebCamInformation::default();
+        assert!(transform(&marked_jpeg(), &info).unwrap().is_none());
+    }
+
+    #[test]
+    fn parse_aspect_ratio_accepts_ratios() {
+        assert_eq!(parse_aspect_ratio("16:9"), Some(16.0 / 9.0));
+        assert_eq!(parse_aspect_ratio(" 4 : 3 "), Some(4.0 / 3.0));
+    }
+
+    #[test]
+    fn parse_aspect_ratio_rejects_invalid_ratios() {
+        assert_eq!(parse_aspect_ratio("16x9"), None);
+        assert_eq!(parse_aspect_ratio("16:"), None);
+        assert_eq!(parse_aspect_ratio("0:9"), None);
+        assert_eq!(parse_aspect_ratio("16:-9"), None);
+    }
+
+    #[test]
+    fn find_jpeg_frame_in_stream() {
+        let buffer = b"--boundary\r\n\r\n\xFF\xD8\x01\x02\xFF\xD9\r\n--boundary";
+        assert_eq!(
+            find_jpeg_frame(buffer),
+            Some(&b"\xFF\xD8\x01\x02\xFF\xD9"[..])
+        );
+    }
+
+    #[test]
+    fn find_jpeg_frame_needs_complete_frame() {
+        assert_eq!(find_jpeg_frame(b"--boundary\r\n\xFF\xD8\x01\x02"), None);
+        assert_eq!(find_jpeg_frame(b"\x01\xFF\xD9"), None);
+        // The end marker cannot overlap the start marker
+        assert_eq!(find_jpeg_frame(b"\xFF\xD8\xD9"), None);
+    }
 }